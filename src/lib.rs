@@ -18,16 +18,39 @@ pub mod puzzle_7;
 pub mod puzzle_8;
 pub mod puzzle_9;
 
-mod util;
+pub mod util;
 
 use std::fmt::Display;
 
+/// Runtime settings for puzzles that have them
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub bag: puzzle_2::Bag,
+    pub mode: puzzle_2::Mode,
+    /// Spelled out digits for part 2 of day 1
+    pub vocabulary: puzzle_1::Vocabulary,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            bag: puzzle_2::Bag::default(),
+            mode: puzzle_2::Mode::default(),
+            vocabulary: puzzle_1::Vocabulary::english(),
+        }
+    }
+}
+
 pub fn solve_puzzle(id: (u32, u32)) -> Box<dyn Display> {
+    solve_puzzle_with(id, &Options::default())
+}
+
+pub fn solve_puzzle_with(id: (u32, u32), options: &Options) -> Box<dyn Display> {
     Box::new(match id {
         (1, 1) => format!("{}", puzzle_1::puzzle_1_1()),
-        (1, 2) => format!("{}", puzzle_1::puzzle_1_2()),
-        (2, 1) => format!("{}", puzzle_2::puzzle_2_1()),
-        (2, 2) => format!("{}", puzzle_2::puzzle_2_2()),
+        (1, 2) => format!("{}", puzzle_1::puzzle_1_2_with(&options.vocabulary)),
+        (2, 1) => format!("{}", puzzle_2::puzzle_2_1_with(&options.bag, options.mode)),
+        (2, 2) => format!("{}", puzzle_2::puzzle_2_2_with(&options.bag, options.mode)),
         (3, 1) => format!("{}", puzzle_3::puzzle_3_1()),
         (3, 2) => format!("{}", puzzle_3::puzzle_3_2()),
         (4, 1) => format!("{}", puzzle_4::puzzle_4_1()),
@@ -43,6 +66,7 @@ pub fn solve_puzzle(id: (u32, u32)) -> Box<dyn Display> {
         (9, 1) => format!("{}", puzzle_9::puzzle_9_1()),
         (9, 2) => format!("{}", puzzle_9::puzzle_9_2()),
         (10, 1) => format!("{}", puzzle_10::puzzle_10_1()),
+        (10, 2) => format!("{}", puzzle_10::puzzle_10_2()),
         (11, 1) => format!("{}", puzzle_11::puzzle_11_1()),
         (11, 2) => format!("{}", puzzle_11::puzzle_11_2()),
         (12, 1) => format!("{}", puzzle_12::puzzle_12_1()),
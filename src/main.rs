@@ -1,7 +1,61 @@
-use aoc32lib::solve_puzzle;
+use aoc32lib::error;
+use aoc32lib::util::log::{self, Filter, Level};
+use aoc32lib::{solve_puzzle_with, Options};
 
 fn main() {
-    if let Some(arg) = std::env::args().skip(1).next() {
+    let mut options = Options::default();
+    let mut verbosity = 0;
+    let mut args = vec![];
+    let mut unknown = vec![];
+    let mut estimate_bag = false;
+    let mut it = std::env::args().skip(1);
+    while let Some(arg) = it.next() {
+        match arg.split_once('=') {
+            Some(("--bag", value)) => options.bag = parse_bag(value),
+            _ if arg == "--bag" => {
+                options.bag = parse_bag(&it.next().expect("--bag needs a value"))
+            }
+            // Infer the day 2 bag, searching up to `--bag`, instead of solving a puzzle
+            _ if arg == "--estimate-bag" => estimate_bag = true,
+            // Skip malformed cubes in day 2 instead of failing
+            _ if arg == "--lenient" => options.mode = aoc32lib::puzzle_2::Mode::Lenient,
+            Some(("--vocabulary", value)) => options.vocabulary = parse_vocabulary(value),
+            _ if arg == "--vocabulary" => {
+                options.vocabulary =
+                    parse_vocabulary(&it.next().expect("--vocabulary needs a value"))
+            }
+            // Each `v` in `-v`, `-vv`, ... raises the default log level one step
+            _ if arg.starts_with('-') => match log::verbosity_flag(&arg) {
+                Some(count) => verbosity += count,
+                None => unknown.push(arg),
+            },
+            _ => args.push(arg),
+        }
+    }
+    let mut filter = Filter::from_env();
+    if verbosity > 0 {
+        filter.default = filter.default.max(Level::from_verbosity(verbosity));
+    }
+    log::init(filter);
+    if !unknown.is_empty() {
+        for flag in unknown {
+            error!("unknown flag {flag:?}");
+        }
+        std::process::exit(2);
+    }
+
+    if estimate_bag {
+        match aoc32lib::puzzle_2::estimate_bag(&options.bag, options.mode) {
+            Ok(estimate) => println!("{estimate}"),
+            Err(e) => {
+                error!("cannot estimate the bag: {e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(arg) = args.first() {
         let mut id_parts = arg.split('.');
         let id = (
             id_parts
@@ -15,10 +69,21 @@ fn main() {
                 .parse::<u32>()
                 .expect("not a number"),
         );
-        let answer = solve_puzzle(id);
+        let answer = solve_puzzle_with(id, &options);
         println!("The answer to puzzle {}.{} is:", id.0, id.1);
         println!("{answer}");
     } else {
         eprintln!("Please specify a puzzle id!");
     }
 }
+
+fn parse_bag(spec: &str) -> aoc32lib::puzzle_2::Bag {
+    spec.parse()
+        .unwrap_or_else(|e| panic!("invalid --bag {spec:?}: {e}"))
+}
+
+/// A built-in vocabulary name or the path of a vocabulary file
+fn parse_vocabulary(spec: &str) -> aoc32lib::puzzle_1::Vocabulary {
+    aoc32lib::puzzle_1::Vocabulary::resolve(spec)
+        .unwrap_or_else(|e| panic!("invalid --vocabulary {spec:?}: {e:#}"))
+}
@@ -1,67 +1,329 @@
+use crate::util::aho_corasick::AhoCorasick;
 use crate::util::load_lines;
-use std::iter::Iterator;
+use crate::{debug, warn};
+use anyhow::{bail, Context, Error, Result};
+use std::fmt::Display;
+use std::ops::Range;
 use std::str::FromStr;
 
 pub fn puzzle_1_1() -> u32 {
-    load_lines("1/input.txt")
-        .map(|l| {
-            l.unwrap()
-                .parse::<Calibration>()
-                .expect("input is parseable")
-        })
-        .map(|c| c.number())
-        .sum()
+    calibration_sum(&DigitScanner::new(&Vocabulary::default())).unwrap()
 }
 
 pub fn puzzle_1_2() -> u32 {
-    load_lines("1/input.txt")
-        .map(|l| {
-            replace_numbers(l.unwrap())
-                .parse::<Calibration>()
-                .expect("input is parseable")
-        })
-        .map(|c| c.number())
-        .sum()
-}
-
-fn replace_numbers(s: String) -> String {
-    let ret = s
-        .replace("one", "o1e")
-        .replace("two", "t2o")
-        .replace("three", "th3ee")
-        .replace("four", "f4ur")
-        .replace("five", "f5ve")
-        .replace("six", "s6x")
-        .replace("seven", "se7en")
-        .replace("eight", "ei8ht")
-        .replace("nine", "n9ne");
-    ret
+    puzzle_1_2_with(&Vocabulary::english())
+}
+
+pub fn puzzle_1_2_with(vocabulary: &Vocabulary) -> u32 {
+    calibration_sum(&DigitScanner::new(vocabulary)).unwrap()
+}
+
+/// Each line's reading is logged at debug level, lines without digits as warnings.
+/// Fails on the first line without digits.
+fn calibration_sum(scanner: &DigitScanner) -> Result<u32> {
+    let lines: Vec<String> = load_lines("1/input.txt").collect::<Result<_, _>>()?;
+    let readings: Vec<Reading> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| Reading::new(i + 1, line, scanner))
+        .collect();
+    for reading in readings.iter() {
+        match reading.digits {
+            Some(_) => debug!("{reading}"),
+            None => warn!("{reading}"),
+        }
+    }
+    total(&readings)
+}
+
+fn total(readings: &[Reading]) -> Result<u32> {
+    readings.iter().map(|r| Ok(r.calibration()?.number())).sum()
+}
+
+/// How every line of `input` is read under `vocabulary`
+pub fn diagnose(input: &str, vocabulary: &Vocabulary) -> Vec<Reading> {
+    let scanner = DigitScanner::new(vocabulary);
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| Reading::new(i + 1, line, &scanner))
+        .collect()
+}
+
+/// Spelled out digits of part 2
+const ENGLISH: &[(&str, u32)] = &[
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+const GERMAN: &[(&str, u32)] = &[
+    ("null", 0),
+    ("eins", 1),
+    ("zwei", 2),
+    ("drei", 3),
+    ("vier", 4),
+    ("fünf", 5),
+    ("sechs", 6),
+    ("sieben", 7),
+    ("acht", 8),
+    ("neun", 9),
+];
+
+const FRENCH: &[(&str, u32)] = &[
+    ("zéro", 0),
+    ("un", 1),
+    ("deux", 2),
+    ("trois", 3),
+    ("quatre", 4),
+    ("cinq", 5),
+    ("six", 6),
+    ("sept", 7),
+    ("huit", 8),
+    ("neuf", 9),
+];
+
+/// Words that stand for digits, on top of the numeric digits themselves
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Vocabulary {
+    pub words: Vec<(String, u32)>,
+    pub ignore_case: bool,
+}
+
+impl Vocabulary {
+    fn from_table(table: &[(&str, u32)], ignore_case: bool) -> Self {
+        Vocabulary {
+            words: table.iter().map(|(w, d)| (w.to_string(), *d)).collect(),
+            ignore_case,
+        }
+    }
+
+    pub fn english() -> Self {
+        Self::from_table(ENGLISH, false)
+    }
+
+    /// A built-in vocabulary: `english`, `english-zero`, `german` or `french`.
+    /// The German and French ones ignore case.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "english" => Some(Self::english()),
+            "english-zero" => {
+                let mut vocabulary = Self::english();
+                vocabulary.words.insert(0, ("zero".to_string(), 0));
+                Some(vocabulary)
+            }
+            "german" => Some(Self::from_table(GERMAN, true)),
+            "french" => Some(Self::from_table(FRENCH, true)),
+            _ => None,
+        }
+    }
+
+    /// Read a vocabulary file; see `FromStr` for the format
+    pub fn load(path: &str) -> Result<Self> {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("reading vocabulary {path}"))?
+            .parse()
+            .with_context(|| format!("in vocabulary {path}"))
+    }
+
+    /// A built-in vocabulary name or else the path of a vocabulary file
+    pub fn resolve(spec: &str) -> Result<Self> {
+        match Self::builtin(spec) {
+            Some(vocabulary) => Ok(vocabulary),
+            None => Self::load(spec),
+        }
+    }
+}
+
+/// One `token digit` pair per line, e.g. `vier 4`.
+/// A line `ignore-case` makes the tokens match in any case; `#` starts a comment.
+impl FromStr for Vocabulary {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut vocabulary = Vocabulary::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line == "ignore-case" {
+                vocabulary.ignore_case = true;
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let [token, digit] = parts[..] else {
+                bail!("line {}: expected `token digit`, got {line:?}", i + 1);
+            };
+            match digit.parse::<u32>() {
+                Ok(d) if d < 10 => vocabulary.words.push((token.to_string(), d)),
+                _ => bail!("line {}: {digit:?} is not a digit", i + 1),
+            }
+        }
+        Ok(vocabulary)
+    }
+}
+
+/// How a digit was written in the line
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Source {
+    Literal,
+    Word,
+}
+
+/// A digit occurrence; `span` holds byte offsets into the line
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Digit {
+    pub value: u32,
+    pub span: Range<usize>,
+    pub source: Source,
+}
+
+/// The digits starting first and last in a line
+fn first_and_last(digits: &[Digit]) -> Option<(Digit, Digit)> {
+    let first = digits.iter().min_by_key(|d| d.span.start)?;
+    let last = digits.iter().max_by_key(|d| d.span.start)?;
+    Some((first.clone(), last.clone()))
+}
+
+/// How one line was read into a calibration value
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Reading {
+    /// 1-based line number
+    pub line: usize,
+    pub text: String,
+    /// First and last digit; `None` if the line has no digits
+    pub digits: Option<(Digit, Digit)>,
+}
+
+impl Reading {
+    fn new(line: usize, text: &str, scanner: &DigitScanner) -> Self {
+        Reading {
+            line,
+            text: text.to_string(),
+            digits: first_and_last(&scanner.scan(text)),
+        }
+    }
+
+    fn calibration(&self) -> Result<Calibration, CalibrationParseError> {
+        let (first, last) = self
+            .digits
+            .as_ref()
+            .ok_or(CalibrationParseError { line: self.line })?;
+        Ok(Calibration(first.value, last.value))
+    }
+
+    pub fn value(&self) -> Option<u32> {
+        self.calibration().ok().map(Calibration::number)
+    }
+
+    fn describe(&self, digit: &Digit) -> String {
+        let source = match digit.source {
+            Source::Literal => "literal",
+            Source::Word => "word",
+        };
+        let token = &self.text[digit.span.clone()];
+        format!(
+            "{} ({source} {token:?} at {}..{})",
+            digit.value, digit.span.start, digit.span.end
+        )
+    }
+}
+
+/// e.g. `line 3 "xtwone3": first 2 (word "two" at 1..4), last 3 (literal "3" at 6..7) = 23`
+impl Display for Reading {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {} {:?}: ", self.line, self.text)?;
+        match (&self.digits, self.value()) {
+            (Some((first, last)), Some(value)) => write!(
+                f,
+                "first {}, last {} = {value}",
+                self.describe(first),
+                self.describe(last)
+            ),
+            _ => write!(f, "no digits"),
+        }
+    }
+}
+
+/// Finds numeric digits and spelled out digit words in one pass, overlaps included
+struct DigitScanner {
+    matcher: AhoCorasick<(u32, Source)>,
+}
+
+impl DigitScanner {
+    /// Numeric digits plus the words of `vocabulary`
+    fn new(vocabulary: &Vocabulary) -> Self {
+        let literals = (0..10).map(|d| (d.to_string(), (d, Source::Literal)));
+        let words = vocabulary
+            .words
+            .iter()
+            .map(|(word, d)| (word.clone(), (*d, Source::Word)));
+        let patterns = literals.chain(words);
+        let matcher = if vocabulary.ignore_case {
+            AhoCorasick::new_ignore_case(patterns)
+        } else {
+            AhoCorasick::new(patterns)
+        };
+        DigitScanner { matcher }
+    }
+
+    /// Every digit in the line, ordered by end offset
+    fn scan(&self, line: &str) -> Vec<Digit> {
+        self.matcher
+            .find_all(line)
+            .into_iter()
+            .map(|m| Digit {
+                value: m.value.0,
+                span: m.span,
+                source: m.value.1,
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
 struct Calibration(u32, u32);
 
 #[derive(Debug)]
-struct CalibrationParseError {}
+struct CalibrationParseError {
+    line: usize,
+}
+
+impl Display for CalibrationParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {} has no digits", self.line)
+    }
+}
+
+impl std::error::Error for CalibrationParseError {}
 
 impl Calibration {
     fn number(self) -> u32 {
         self.0 * 10 + self.1
     }
+
+    /// Built from the digits starting first and last in `text`, the 1-based `line` of its input
+    fn scan(
+        line: usize,
+        text: &str,
+        scanner: &DigitScanner,
+    ) -> Result<Self, CalibrationParseError> {
+        Reading::new(line, text, scanner).calibration()
+    }
 }
 
 impl FromStr for Calibration {
     type Err = CalibrationParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut iter = s.chars().filter(|c| c.is_digit(10)).map(|c| {
-            let mut buf = [0u8; 4];
-            let s = c.encode_utf8(&mut buf);
-            str::parse::<u32>(s).expect("single digits are parseable")
-        });
-        let first = iter.next().ok_or(CalibrationParseError {})?;
-        let last = iter.last().unwrap_or(first);
-        Ok(Calibration(first, last))
+        Calibration::scan(1, s, &DigitScanner::new(&Vocabulary::default()))
     }
 }
 
@@ -85,4 +347,109 @@ mod test {
             assert_eq!(Calibration(expected.0, expected.1), cal);
         }
     }
+
+    const EXAMPLE_2: &[(&str, u32)] = &[
+        ("two1nine", 29),
+        ("eightwothree", 83),
+        ("abcone2threexyz", 13),
+        ("xtwone3four", 24),
+        ("4nineeightseven2", 42),
+        ("zoneight234", 14),
+        ("7pqrstsixteen", 76),
+        ("eightwo", 82),
+    ];
+
+    #[test]
+    fn test_spelled_out() {
+        let scanner = DigitScanner::new(&Vocabulary::english());
+        for (input, expected) in EXAMPLE_2.iter() {
+            let cal = Calibration::scan(1, input, &scanner).expect("examples should be valid");
+            assert_eq!(*expected, cal.number(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_scan_offsets() {
+        let digits = DigitScanner::new(&Vocabulary::english()).scan("xtwone3");
+        let expected = vec![
+            Digit {
+                value: 2,
+                span: 1..4,
+                source: Source::Word,
+            },
+            Digit {
+                value: 1,
+                span: 3..6,
+                source: Source::Word,
+            },
+            Digit {
+                value: 3,
+                span: 6..7,
+                source: Source::Literal,
+            },
+        ];
+        assert_eq!(expected, digits);
+        assert!("abc".parse::<Calibration>().is_err());
+        let scanner = DigitScanner::new(&Vocabulary::english());
+        let err = Calibration::scan(7, "abc", &scanner).unwrap_err();
+        assert_eq!("line 7 has no digits", err.to_string());
+    }
+
+    fn number(line: &str, vocabulary: &Vocabulary) -> u32 {
+        Calibration::scan(1, line, &DigitScanner::new(vocabulary))
+            .expect("examples should be valid")
+            .number()
+    }
+
+    #[test]
+    fn test_builtin_vocabularies() {
+        let german = Vocabulary::builtin("German").unwrap();
+        assert_eq!(50, number("xFünfachtNULL", &german));
+        assert_eq!(38, number("dreiundzwanzigacht", &german));
+
+        let french = Vocabulary::builtin("french").unwrap();
+        assert_eq!(16, number("UNEdeuxsix", &french));
+        assert_eq!(30, number("troiszéro", &french));
+
+        let zero = Vocabulary::builtin("english-zero").unwrap();
+        assert_eq!(1, number("zeroneZERO", &zero));
+        assert!(Vocabulary::builtin("klingon").is_none());
+    }
+
+    #[test]
+    fn test_vocabulary_file() {
+        let vocabulary: Vocabulary = "# mixed case tokens\nONE 1\nTwo 2 # trailing\n\nnil 0\n"
+            .parse()
+            .unwrap();
+        assert!(!vocabulary.ignore_case);
+        assert_eq!(10, number("ONEnilone", &vocabulary));
+        assert_eq!(22, number("twoTwo", &vocabulary));
+
+        let vocabulary: Vocabulary = "ignore-case\nONE 1\n".parse().unwrap();
+        assert_eq!(11, number("One", &vocabulary));
+
+        let err = "one 1\ntwo 12\n".parse::<Vocabulary>().unwrap_err();
+        assert_eq!("line 2: \"12\" is not a digit", err.to_string());
+        assert!("one\n".parse::<Vocabulary>().is_err());
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let readings = diagnose("xtwone3\nabc\n7", &Vocabulary::english());
+        assert_eq!(3, readings.len());
+        assert_eq!(
+            "line 1 \"xtwone3\": first 2 (word \"two\" at 1..4), last 3 (literal \"3\" at 6..7) = 23",
+            readings[0].to_string()
+        );
+        assert_eq!(None, readings[1].value());
+        assert_eq!("line 2 \"abc\": no digits", readings[1].to_string());
+        assert_eq!(Some(77), readings[2].value());
+        let (first, last) = readings[2].digits.as_ref().unwrap();
+        assert_eq!(first, last);
+
+        let err = total(&readings).unwrap_err();
+        assert_eq!("line 2 has no digits", err.to_string());
+        let lines = [readings[0].clone(), readings[2].clone()];
+        assert_eq!(23 + 77, total(&lines).unwrap());
+    }
 }
@@ -1,4 +1,6 @@
-use crate::util::load_file;
+use crate::util::geom::{Direction, Pos};
+use crate::util::polygon::Polygon;
+use crate::util::{checked, load_file};
 use anyhow::Error;
 use std::ops::Index;
 use std::str::FromStr;
@@ -11,7 +13,10 @@ pub fn puzzle_10_1() -> usize {
 }
 
 pub fn puzzle_10_2() -> u64 {
-    0
+    load_file("10/input.txt")
+        .parse::<Pipes>()
+        .expect("valid pipe input")
+        .enclosed() as u64
 }
 
 #[derive(Debug, PartialEq)]
@@ -48,15 +53,19 @@ struct Pipes {
     data: Vec<Pipe>,
 }
 
-type Coordinate = (usize, usize);
-type Connections = [Coordinate; 2];
-type Step = [Coordinate; 2];
+type Connections = [Pos; 2];
+type Step = [Pos; 2];
 
-impl Index<Coordinate> for Pipes {
+impl Index<Pos> for Pipes {
     type Output = Pipe;
 
-    fn index(&self, index: Coordinate) -> &Self::Output {
-        &self.data[index.0 * self.rowlen + index.1]
+    fn index(&self, index: Pos) -> &Self::Output {
+        let rows = (self.data.len() / self.rowlen) as isize;
+        if (0..rows).contains(&index.row) && (0..self.rowlen as isize).contains(&index.col) {
+            &self.data[index.row as usize * self.rowlen + index.col as usize]
+        } else {
+            &Pipe::None
+        }
     }
 }
 
@@ -75,43 +84,38 @@ impl FromStr for Pipes {
 }
 
 impl Pipes {
-    fn connected(&self, coord: Coordinate) -> Connections {
+    fn connected(&self, coord: Pos) -> Connections {
+        use Direction::*;
         use Pipe::*;
-        match self[coord] {
-            Start => self.start_connections(coord), // S
-            WestEast => [(coord.0, coord.1 - 1), (coord.0, coord.1 + 1)], // -
-            NorthSouth => [(coord.0 - 1, coord.1), (coord.0 + 1, coord.1)], // \
-            NorthWest => [(coord.0 - 1, coord.1), (coord.0, coord.1 - 1)], // J
-            NorthEast => [(coord.0 - 1, coord.1), (coord.0, coord.1 + 1)], // L
-            SouthWest => [(coord.0, coord.1 - 1), (coord.0 + 1, coord.1)], // 7
-            SouthEast => [(coord.0, coord.1 + 1), (coord.0 + 1, coord.1)], // F
-            None => [coord, coord],
-        }
+        let [a, b] = match self[coord] {
+            Start => return self.start_connections(coord), // S
+            WestEast => [West, East],                      // -
+            NorthSouth => [North, South],                  // |
+            NorthWest => [North, West],                    // J
+            NorthEast => [North, East],                    // L
+            SouthWest => [West, South],                    // 7
+            SouthEast => [East, South],                    // F
+            None => return [coord, coord],
+        };
+        [coord.step(a), coord.step(b)]
     }
 
-    fn start_connections(&self, coord: Coordinate) -> Connections {
-        let mut connected = vec![];
-        for r in coord.0 - 1..=coord.0 + 1 {
-            for c in coord.1 - 1..=coord.1 + 1 {
-                if r == coord.0 && c == coord.1 {
-                    continue;
-                }
-                let incoming = self.connected((r, c));
-                if incoming[0] == coord || incoming[1] == coord {
-                    connected.push((r, c));
-                }
-            }
-        }
+    fn start_connections(&self, coord: Pos) -> Connections {
+        let connected: Vec<Pos> = coord
+            .neighbours()
+            .into_iter()
+            .filter(|&n| self.connected(n).contains(&coord))
+            .collect();
         [connected[0], connected[1]]
     }
 
-    fn start(&self) -> Coordinate {
+    fn start(&self) -> Pos {
         let pos = self
             .data
             .iter()
             .position(|p| p == &Pipe::Start)
             .expect("there should be a start");
-        (pos % self.rowlen, pos / self.rowlen)
+        Pos::from((pos / self.rowlen, pos % self.rowlen))
     }
 
     fn step(&self, step: Step) -> Step {
@@ -123,19 +127,32 @@ impl Pipes {
         }
     }
 
-    fn pathlength(&self) -> usize {
-        let start: Coordinate = self.start();
+    /// Every tile of the loop, in order, starting at the start tile
+    fn tiles(&self) -> Vec<Pos> {
+        let start: Pos = self.start();
         let start_connections = self.connected(start);
         let mut step = [start, start_connections[0]];
-        let mut len = 0;
+        let mut tiles = vec![start];
         loop {
             step = self.step(step);
-            len += 1;
             if step[0] == start {
-                return len / 2;
+                return tiles;
             }
+            tiles.push(step[0]);
         }
     }
+
+    fn pathlength(&self) -> usize {
+        self.tiles().len() / 2
+    }
+
+    /// Tiles strictly enclosed by the loop
+    fn enclosed(&self) -> usize {
+        let interior = Polygon::new(self.tiles())
+            .interior_points()
+            .expect("pipe loop is a simple polygon");
+        checked::convert(10, interior)
+    }
 }
 
 #[cfg(test)]
@@ -147,41 +164,68 @@ mod test {
     #[test]
     fn test_parse_pipes() {
         let pipes = EXAMPLE.parse::<Pipes>().unwrap();
-        assert_eq!(Pipe::None, pipes[(0, 0)]);
-        assert_eq!(Pipe::None, pipes[(2, 2)]);
-        assert_eq!(Pipe::None, pipes[(4, 4)]);
+        assert_eq!(Pipe::None, pipes[Pos::new(0, 0)]);
+        assert_eq!(Pipe::None, pipes[Pos::new(2, 2)]);
+        assert_eq!(Pipe::None, pipes[Pos::new(4, 4)]);
 
-        assert_eq!(Pipe::Start, pipes[(1, 1)]);
-        assert_eq!(Pipe::WestEast, pipes[(1, 2)]);
-        assert_eq!(Pipe::SouthWest, pipes[(1, 3)]);
-        assert_eq!(Pipe::NorthSouth, pipes[(2, 3)]);
-        assert_eq!(Pipe::NorthWest, pipes[(3, 3)]);
-        assert_eq!(Pipe::WestEast, pipes[(3, 2)]);
-        assert_eq!(Pipe::NorthEast, pipes[(3, 1)]);
-        assert_eq!(Pipe::NorthSouth, pipes[(2, 1)]);
+        assert_eq!(Pipe::Start, pipes[Pos::new(1, 1)]);
+        assert_eq!(Pipe::WestEast, pipes[Pos::new(1, 2)]);
+        assert_eq!(Pipe::SouthWest, pipes[Pos::new(1, 3)]);
+        assert_eq!(Pipe::NorthSouth, pipes[Pos::new(2, 3)]);
+        assert_eq!(Pipe::NorthWest, pipes[Pos::new(3, 3)]);
+        assert_eq!(Pipe::WestEast, pipes[Pos::new(3, 2)]);
+        assert_eq!(Pipe::NorthEast, pipes[Pos::new(3, 1)]);
+        assert_eq!(Pipe::NorthSouth, pipes[Pos::new(2, 1)]);
     }
 
     #[test]
     fn test_connections() {
         let pipes = EXAMPLE.parse::<Pipes>().unwrap();
-        assert_eq!([(0, 0), (0, 0)], pipes.connected((0, 0)));
+        assert_eq!(
+            [Pos::new(0, 0), Pos::new(0, 0)],
+            pipes.connected(Pos::new(0, 0))
+        );
 
-        assert_eq!([(1, 2), (2, 1)], pipes.connected((1, 1)));
-        assert_eq!([(1, 1), (1, 3)], pipes.connected((1, 2)));
-        assert_eq!([(1, 2), (2, 3)], pipes.connected((1, 3)));
+        assert_eq!(
+            [Pos::new(1, 2), Pos::new(2, 1)],
+            pipes.connected(Pos::new(1, 1))
+        );
+        assert_eq!(
+            [Pos::new(1, 1), Pos::new(1, 3)],
+            pipes.connected(Pos::new(1, 2))
+        );
+        assert_eq!(
+            [Pos::new(1, 2), Pos::new(2, 3)],
+            pipes.connected(Pos::new(1, 3))
+        );
 
-        assert_eq!([(1, 1), (3, 1)], pipes.connected((2, 1)));
-        assert_eq!([(1, 3), (3, 3)], pipes.connected((2, 3)));
+        assert_eq!(
+            [Pos::new(1, 1), Pos::new(3, 1)],
+            pipes.connected(Pos::new(2, 1))
+        );
+        assert_eq!(
+            [Pos::new(1, 3), Pos::new(3, 3)],
+            pipes.connected(Pos::new(2, 3))
+        );
 
-        assert_eq!([(2, 1), (3, 2)], pipes.connected((3, 1)));
-        assert_eq!([(3, 1), (3, 3)], pipes.connected((3, 2)));
-        assert_eq!([(2, 3), (3, 2)], pipes.connected((3, 3)));
+        assert_eq!(
+            [Pos::new(2, 1), Pos::new(3, 2)],
+            pipes.connected(Pos::new(3, 1))
+        );
+        assert_eq!(
+            [Pos::new(3, 1), Pos::new(3, 3)],
+            pipes.connected(Pos::new(3, 2))
+        );
+        assert_eq!(
+            [Pos::new(2, 3), Pos::new(3, 2)],
+            pipes.connected(Pos::new(3, 3))
+        );
     }
 
     #[test]
     fn test_get_start() {
         let pipes = EXAMPLE.parse::<Pipes>().unwrap();
-        assert_eq!((1, 1), pipes.start());
+        assert_eq!(Pos::new(1, 1), pipes.start());
     }
 
     #[test]
@@ -191,11 +235,11 @@ mod test {
         let start_connections = pipes.connected(start);
         let step = [start, start_connections[0]];
         let next = pipes.step(step);
-        let expected = [(1, 2), (1, 3)];
+        let expected = [Pos::new(1, 2), Pos::new(1, 3)];
         assert_eq!(expected, next);
 
         let next = pipes.step(next);
-        let expected = [(1, 3), (2, 3)];
+        let expected = [Pos::new(1, 3), Pos::new(2, 3)];
         assert_eq!(expected, next)
     }
 
@@ -203,5 +247,22 @@ mod test {
     fn test_example() {
         let pipes = EXAMPLE.parse::<Pipes>().unwrap();
         assert_eq!(4, pipes.pathlength());
+        assert_eq!(1, pipes.enclosed());
+    }
+
+    #[test]
+    fn test_enclosed() {
+        let pipes = "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+..........."
+            .parse::<Pipes>()
+            .unwrap();
+        assert_eq!(4, pipes.enclosed());
     }
 }
@@ -1,3 +1,4 @@
+use crate::util::geom::Pos;
 use crate::util::load_lines;
 use std::collections::BTreeSet;
 
@@ -15,53 +16,41 @@ fn puzzle(expansion_rate: usize) -> u64 {
     universe.distances().into_iter().sum::<usize>() as u64
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
-struct Coordinate {
-    row: usize,
-    col: usize,
-}
-
 #[derive(Debug, Clone)]
 struct Distance {
-    from: Coordinate,
-    to: Coordinate,
+    from: Pos,
+    to: Pos,
 }
 
 impl Distance {
     fn length(&self, expansion: &Expansion) -> usize {
-        let start_row = usize::min(self.from.row, self.to.row);
-        let stop_row = usize::max(self.from.row, self.to.row);
-        let start_col = usize::min(self.from.col, self.to.col);
-        let stop_col = usize::max(self.from.col, self.to.col);
-        let mut delta_row = stop_row - start_row;
-        let mut delta_col = stop_col - start_col;
-
-        for e in expansion.rows.iter() {
-            if start_row < *e && *e < stop_row {
-                delta_row += expansion.rate - 1;
-            }
-        }
-
-        for e in expansion.cols.iter() {
-            if start_col < *e && *e < stop_col {
-                delta_col += expansion.rate - 1;
-            }
-        }
-
-        delta_row + delta_col
+        let between = |e: isize, a: isize, b: isize| a.min(b) < e && e < a.max(b);
+        let expanded = expansion
+            .rows
+            .iter()
+            .filter(|&&e| between(e, self.from.row, self.to.row))
+            .chain(
+                expansion
+                    .cols
+                    .iter()
+                    .filter(|&&e| between(e, self.from.col, self.to.col)),
+            )
+            .count();
+
+        self.from.manhattan(self.to) + expanded * (expansion.rate - 1)
     }
 }
 
 #[derive(Debug)]
 struct Universe {
-    galaxies: BTreeSet<Coordinate>,
+    galaxies: BTreeSet<Pos>,
     expansion: Expansion,
 }
 
 #[derive(Debug, PartialEq, Eq)]
 struct Expansion {
-    rows: Vec<usize>,
-    cols: Vec<usize>,
+    rows: Vec<isize>,
+    cols: Vec<isize>,
     rate: usize,
 }
 
@@ -75,27 +64,27 @@ impl Universe {
         let mut max_row = 0;
         let mut max_col = 0;
         for (row, line) in lines.enumerate() {
-            max_col = line.as_ref().len();
+            max_col = line.as_ref().len() as isize;
             for (col, symbol) in line.as_ref().chars().enumerate() {
                 if symbol == '#' {
-                    galaxies.insert(Coordinate { row, col });
+                    galaxies.insert(Pos::from((row, col)));
                 }
             }
-            max_row = row;
+            max_row = row as isize;
         }
 
         let expansion = Expansion {
             rows: (0..max_row)
                 .filter(|&row| {
                     (0..max_col)
-                        .find_map(|col| galaxies.get(&Coordinate { row, col }))
+                        .find_map(|col| galaxies.get(&Pos::new(row, col)))
                         .is_none()
                 })
                 .collect(),
             cols: (0..max_row)
                 .filter(|&col| {
                     (0..max_col)
-                        .find_map(|row| galaxies.get(&Coordinate { row, col }))
+                        .find_map(|row| galaxies.get(&Pos::new(row, col)))
                         .is_none()
                 })
                 .collect(),
@@ -114,10 +103,7 @@ impl Universe {
         loop {
             if let Some(g0) = galaxies.pop_first() {
                 for g1 in galaxies.iter() {
-                    let dist = Distance {
-                        from: g0.clone(),
-                        to: g1.clone(),
-                    };
+                    let dist = Distance { from: g0, to: *g1 };
                     distances.push(dist.length(&self.expansion));
                 }
             } else {
@@ -138,9 +124,9 @@ mod test {
     fn test_parse_universe() {
         let lines = EXAMPLE.lines();
         let universe = Universe::new(lines, 2);
-        assert!(universe.galaxies.contains(&Coordinate { row: 0, col: 3 }));
-        assert!(universe.galaxies.contains(&Coordinate { row: 1, col: 7 }));
-        assert!(universe.galaxies.contains(&Coordinate { row: 9, col: 0 }));
+        assert!(universe.galaxies.contains(&Pos::new(0, 3)));
+        assert!(universe.galaxies.contains(&Pos::new(1, 7)));
+        assert!(universe.galaxies.contains(&Pos::new(9, 0)));
     }
 
     #[test]
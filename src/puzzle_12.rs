@@ -1,5 +1,5 @@
-#![allow(unused)]
 use crate::util::load_lines;
+use crate::util::memo::Memo;
 use anyhow::Error;
 use std::str::FromStr;
 
@@ -32,7 +32,6 @@ enum Spring {
     Op,
     Dmg,
     Tbd,
-    End,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -55,28 +54,6 @@ impl FromStr for Springs {
     }
 }
 
-impl Springs {
-    fn complete_groups(&self) -> Vec<usize> {
-        self.0
-            .iter()
-            .chain(&Some(Spring::End)) // Insert end marker -> Options are iterable once
-            .scan(0usize, |state, s| match s {
-                Spring::Tbd => None,
-                Spring::Dmg => {
-                    *state += 1;
-                    Some(0)
-                }
-                Spring::Op | Spring::End => {
-                    let group = *state;
-                    *state = 0;
-                    Some(group)
-                }
-            })
-            .filter(|g| *g > 0)
-            .collect()
-    }
-}
-
 #[derive(Debug, PartialEq, Eq, Clone)]
 struct Stage {
     springs: Springs,
@@ -84,67 +61,41 @@ struct Stage {
 }
 
 impl Stage {
-    fn repr(&self) -> String {
-        self.springs
-            .0
-            .iter()
-            .map(|s| match s {
-                Spring::Op => '.',
-                Spring::Dmg => '#',
-                Spring::Tbd => '?',
-                _ => 'X',
-            })
-            .collect()
-    }
-
-    fn done(&self) -> bool {
-        self.springs.0.iter().find(|s| s == &&Spring::Tbd).is_none()
+    /// Number of ways to fill in the unknown springs that match the stretches
+    fn possibilities(&self) -> usize {
+        Memo::new().solve((0, 0), &|calls, &(spring, stretch)| {
+            self.arrangements(spring, stretch, calls)
+        })
     }
 
-    fn possible(&self) -> bool {
-        if self.done() {
-            self.stretches == self.springs.complete_groups()
-        } else {
-            possible(&self.stretches, &self.springs.complete_groups())
+    /// Arrangements of `springs[spring..]` producing `stretches[stretch..]`;
+    /// `calls` counts the arrangements of a later state
+    fn arrangements(
+        &self,
+        spring: usize,
+        stretch: usize,
+        calls: &mut dyn FnMut((usize, usize)) -> usize,
+    ) -> usize {
+        let springs = &self.springs.0;
+        let Some(first) = springs.get(spring) else {
+            return (stretch == self.stretches.len()) as usize;
+        };
+        let mut count = 0;
+        if *first != Spring::Dmg {
+            count += calls((spring + 1, stretch));
         }
-    }
-
-    fn possibilities(&self) -> usize {
-        if let Some((left, right)) = self.split() {
-            if !left.possible() {
-                return right.possibilities();
-            } else if !right.possible() {
-                return left.possibilities();
-            } else {
-                return left.possibilities() + right.possibilities();
-            }
-        } else {
-            if self.possible() {
-                return 1;
-            } else {
-                return 0;
+        if *first != Spring::Op {
+            if let Some(&len) = self.stretches.get(stretch) {
+                let end = spring + len;
+                let fits = end <= springs.len()
+                    && springs[spring..end].iter().all(|s| *s != Spring::Op)
+                    && springs.get(end) != Some(&Spring::Dmg);
+                if fits {
+                    count += calls((end + 1, stretch + 1));
+                }
             }
         }
-    }
-
-    fn first_tbd(&mut self) -> Option<&mut Spring> {
-        self.springs.0.iter_mut().find(|s| **s == Spring::Tbd)
-    }
-
-    fn split(&self) -> Option<(Stage, Stage)> {
-        let mut left = self.clone();
-        let mut right = self.clone();
-        *(left.first_tbd()?) = Spring::Op;
-        *(right.first_tbd()?) = Spring::Dmg;
-        Some((left, right))
-    }
-}
-
-fn possible(stretches: &[usize], groups: &[usize]) -> bool {
-    if groups.len() > stretches.len() {
-        false
-    } else {
-        stretches.iter().zip(groups).all(|(s, g)| g == s)
+        count
     }
 }
 
@@ -153,94 +104,6 @@ mod test {
     use super::*;
     use Spring::*;
 
-    #[test]
-    fn test_possible() {
-        assert!(possible(&[1, 1, 3], &[]));
-        assert!(possible(&[1, 1, 3], &[1]));
-        assert!(possible(&[1, 1, 3], &[1, 1]));
-        assert!(possible(&[1, 1, 3], &[1, 1, 3]));
-
-        assert!(!possible(&[1, 1, 3], &[1, 1, 2]));
-        assert!(!possible(&[1, 1, 3], &[1, 1, 3, 1]));
-        assert!(!possible(&[1, 1, 3], &[2]));
-        assert!(!possible(&[1, 1, 3], &[1, 1, 4]));
-    }
-
-    #[test]
-    fn test_possible_stages() {
-        let stage = Stage {
-            springs: Springs(vec![Tbd, Tbd, Tbd, Op, Dmg, Dmg, Dmg]),
-            stretches: vec![1, 1, 3],
-        };
-        assert!(stage.possible());
-
-        let stage = Stage {
-            springs: Springs(vec![Op, Op, Dmg, Op, Dmg, Dmg, Dmg]),
-            stretches: vec![1, 1, 3],
-        };
-        assert!(!stage.possible());
-
-        let stage = Stage {
-            springs: Springs(vec![Dmg, Op, Dmg, Op, Dmg, Dmg, Dmg]),
-            stretches: vec![1, 1, 3],
-        };
-        assert!(stage.possible());
-
-        let stage = Stage {
-            springs: Springs(vec![Dmg, Dmg, Op, Tbd, Tbd, Tbd]),
-            stretches: vec![1, 1, 3],
-        };
-        assert!(!stage.possible());
-
-        let stage = Stage {
-            springs: Springs(vec![Dmg, Dmg, Op, Dmg, Dmg]),
-            stretches: vec![2, 2, 1],
-        };
-        assert!(!stage.possible());
-    }
-
-    #[test]
-    fn test_split() {
-        let stage = Stage {
-            springs: Springs(vec![Tbd, Tbd, Tbd, Op, Dmg, Dmg, Dmg]),
-            stretches: vec![1, 1, 3],
-        };
-        let left = Stage {
-            springs: Springs(vec![Op, Tbd, Tbd, Op, Dmg, Dmg, Dmg]),
-            stretches: vec![1, 1, 3],
-        };
-        let right = Stage {
-            springs: Springs(vec![Dmg, Tbd, Tbd, Op, Dmg, Dmg, Dmg]),
-            stretches: vec![1, 1, 3],
-        };
-
-        assert_eq!(Some((left, right)), stage.split());
-    }
-
-    #[test]
-    fn test_groups() {
-        let empty = Vec::<usize>::new();
-        assert_eq!(
-            empty,
-            Springs(vec![Tbd, Tbd, Op, Op, Dmg, Tbd, Dmg]).complete_groups()
-        );
-
-        assert_eq!(
-            empty,
-            Springs(vec![Dmg, Tbd, Op, Op, Dmg, Tbd, Dmg]).complete_groups()
-        );
-
-        assert_eq!(
-            vec![2],
-            Springs(vec![Dmg, Dmg, Op, Op, Dmg, Tbd, Dmg]).complete_groups()
-        );
-
-        assert_eq!(
-            vec![2, 1, 1],
-            Springs(vec![Dmg, Dmg, Op, Op, Dmg, Op, Dmg]).complete_groups()
-        );
-    }
-
     #[test]
     fn test_possibilities() {
         let stage = Stage {
@@ -290,4 +153,19 @@ mod test {
         };
         assert_eq!(10, stage.possibilities());
     }
+
+    #[test]
+    fn test_unfolded() {
+        let stage = Stage {
+            springs: [".??..??...?##."; 5].join("?").parse().unwrap(),
+            stretches: [1, 1, 3].repeat(5),
+        };
+        assert_eq!(16384, stage.possibilities());
+
+        let stage = Stage {
+            springs: ["?###????????"; 5].join("?").parse().unwrap(),
+            stretches: [3, 2, 1].repeat(5),
+        };
+        assert_eq!(506250, stage.possibilities());
+    }
 }
@@ -1,4 +1,5 @@
-use crate::util::{load_lines, transpose};
+use crate::util::{load_lines, Grid};
+use crate::{debug, trace};
 
 pub fn puzzle_13_1() -> u64 {
     let lines: Vec<String> = load_lines("13/input.txt")
@@ -15,12 +16,12 @@ fn calc_score(lines: Vec<String>) -> u64 {
         if !l.is_empty() {
             pattern.push(l.clone());
         } else {
-            dbg!(pattern.len());
-            total_score += Pattern::new(&pattern).score();
+            trace!("pattern with {} rows", pattern.len());
+            total_score += Pattern::new(&to_grid(&pattern)).score();
             pattern.clear();
         }
     }
-    total_score += Pattern::new(&pattern).score();
+    total_score += Pattern::new(&to_grid(&pattern)).score();
 
     total_score
 }
@@ -32,10 +33,12 @@ struct Pattern {
 }
 
 impl Pattern {
-    fn new(data: &Vec<String>) -> Self {
-        let parser = |s: &String| return str_to_num(&s[..], '#');
-        let rows: Vec<u32> = data.iter().map(parser).collect();
-        let cols: Vec<u32> = transpose(data).iter().map(parser).collect();
+    fn new(data: &Grid<char>) -> Self {
+        let rows: Vec<u32> = data.rows().map(|r| cells_to_num(r.iter(), '#')).collect();
+        let cols: Vec<u32> = data
+            .columns()
+            .map(|c| cells_to_num(c.iter(), '#'))
+            .collect();
         Pattern { rows, cols }
     }
 
@@ -43,7 +46,10 @@ impl Pattern {
         let cmp = |(&a, &b)| a == b;
         let c = find_palindrome(&self.cols, cmp).unwrap_or(0);
         let r = find_palindrome(&self.rows, cmp).unwrap_or(0);
-        dbg!(&self.rows, &self.cols, r, c);
+        debug!(
+            "rows {:?}, cols {:?}: mirrored after row {r}, column {c}",
+            self.rows, self.cols
+        );
         (c + 100 * r) as u64
     }
 }
@@ -61,11 +67,12 @@ where
     None
 }
 
-fn str_to_num(s: &str, marker: char) -> u32 {
-    s.chars()
-        .rev()
-        .enumerate()
-        .fold(0, |num, (i, c)| num + if c == marker { 1 << i } else { 0 })
+fn to_grid(lines: &[String]) -> Grid<char> {
+    Grid::from_table_data(&lines.join("\n"))
+}
+
+fn cells_to_num<'a>(cells: impl Iterator<Item = &'a char>, marker: char) -> u32 {
+    cells.fold(0, |num, c| (num << 1) + if *c == marker { 1 } else { 0 })
 }
 
 #[cfg(test)]
@@ -80,4 +87,12 @@ mod test {
 
         assert_eq!(405, calc_score(lines));
     }
+
+    #[test]
+    fn test_single_row() {
+        let grid = to_grid(&["#..#".to_string()]);
+        assert_eq!((1, 4), grid.shape);
+        // Only a vertical mirror is possible, between columns 2 and 3
+        assert_eq!(2, Pattern::new(&grid).score());
+    }
 }
@@ -1,19 +1,20 @@
-use crate::util::{load_lines, transpose};
+use crate::util::{load_file, Grid};
 
 pub fn puzzle_14_1() -> u64 {
-    let lines: Vec<String> = load_lines("14/input.txt")
-        .collect::<Result<Vec<String>, _>>()
-        .expect("valid input");
+    let platform = Grid::<char>::from_table_data(&load_file("14/input.txt"));
 
-    transpose(&lines)
-        .iter()
-        .map(|s| weight_after_shift(s))
+    platform
+        .columns()
+        .map(|col| weight_after_shift(col.iter().copied()))
         .sum::<usize>() as u64
 }
 
-fn weight_after_shift(s: &str) -> usize {
-    let south_dist = s.len();
-    s.chars()
+fn weight_after_shift<I>(rocks: I) -> usize
+where
+    I: ExactSizeIterator<Item = char>,
+{
+    let south_dist = rocks.len();
+    rocks
         .enumerate()
         .scan(south_dist, |weight, (i, rock)| match rock {
             'O' => {
@@ -33,6 +34,7 @@ fn weight_after_shift(s: &str) -> usize {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::util::transpose;
 
     #[test]
     fn test_transpose() {
@@ -60,6 +62,7 @@ mod test {
         //                 26               9
         // Sum = 32+28+27+26+19+18+11+10+9+8+7+1 = 196
 
-        assert_eq!(196, weight_after_shift(&input));
+        let rocks: Vec<char> = input.chars().collect();
+        assert_eq!(196, weight_after_shift(rocks.into_iter()));
     }
 }
@@ -1,7 +1,9 @@
+use std::cmp::Reverse;
 use std::fmt::Display;
-use std::{cmp::Reverse, iter::repeat_with};
 
-use crate::util::{load_file, Grid};
+use crate::util::geom::{Direction, Pos};
+use crate::util::Grid;
+
 pub fn puzzle_17_1() -> u64 {
     0
 }
@@ -16,38 +18,10 @@ struct HeatField {
 struct TraversalData {
     total_heat_loss: u32,
     straight_steps: u8,
-    position: Position,
+    position: Pos,
     entry: Direction,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
-struct Position {
-    row: usize,
-    col: usize,
-}
-
-#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
-#[repr(usize)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Position {
-    fn new(row: isize, col: isize) -> Option<Position> {
-        if row < 0 || col < 0 {
-            None
-        } else {
-            Some(Position {
-                row: row as usize,
-                col: col as usize,
-            })
-        }
-    }
-}
-
 impl Display for HeatField {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("{}", self.heat_loss))
@@ -72,15 +46,7 @@ fn get_straight_steps(steps: u8, enter: Direction, exit: Direction) -> u8 {
 }
 
 impl Grid<HeatField> {
-    fn at(&self, pos: &Position) -> &HeatField {
-        &self[pos.row][pos.col]
-    }
-
-    fn at_mut(&mut self, pos: &Position) -> &mut HeatField {
-        &mut self[pos.row][pos.col]
-    }
-
-    fn best_path(&mut self, from: Position, to: Position) {
+    fn best_path(&mut self, from: Pos, to: Pos) {
         let mut path_heap = std::collections::BinaryHeap::<Reverse<(u32, TraversalData)>>::new();
         path_heap.push(Reverse((
             0,
@@ -97,12 +63,12 @@ impl Grid<HeatField> {
                 if trv.straight_steps > 2 {
                     continue;
                 };
-                self.at_mut(&trv.position).traversal = Some(trv.clone());
-                for (dir, conn) in self.connections(&trv.position).into_iter() {
+                self[trv.position].traversal = Some(trv.clone());
+                for (dir, conn) in self.connections(trv.position) {
                     path_heap.push(Reverse((
-                        trv.total_heat_loss + self.at(&conn).heat_loss,
+                        trv.total_heat_loss + self[conn].heat_loss,
                         TraversalData {
-                            total_heat_loss: trv.total_heat_loss + self.at(&conn).heat_loss,
+                            total_heat_loss: trv.total_heat_loss + self[conn].heat_loss,
                             straight_steps: get_straight_steps(trv.straight_steps, trv.entry, dir),
                             position: conn,
                             entry: dir,
@@ -118,30 +84,11 @@ impl Grid<HeatField> {
         }
     }
 
-    fn connections(&self, pos: &Position) -> Vec<(Direction, Position)> {
-        let r = pos.row as isize;
-        let c = pos.col as isize;
-        let raw: [(Direction, (isize, isize)); 4] = [
-            (Direction::North, (r + 1, c)),
-            (Direction::East, (r, c - 1)),
-            (Direction::South, (r - 1, c)),
-            (Direction::West, (r, c + 1)),
-        ];
-
-        raw.into_iter()
-            .filter(|(_, (r, c))| {
-                *r >= 0 && *c >= 0 && (*r as usize) < self.shape.0 && (*c as usize) < self.shape.1
-            })
-            .map(|(d, (row, col))| {
-                (
-                    d,
-                    Position {
-                        row: row as usize,
-                        col: col as usize,
-                    },
-                )
-            })
-            .filter(|(_, p)| self.at(p).traversal.is_none())
+    fn connections(&self, pos: Pos) -> Vec<(Direction, Pos)> {
+        Direction::ALL
+            .into_iter()
+            .map(|dir| (dir, pos.step(dir)))
+            .filter(|(_, p)| matches!(self.at(*p), Some(field) if field.traversal.is_none()))
             .collect()
     }
 }
@@ -149,6 +96,7 @@ impl Grid<HeatField> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::util::load_file;
 
     #[test]
     fn test_grid() {
@@ -162,8 +110,8 @@ mod test {
     fn test_traversal() {
         let data = load_file("17/example.txt");
         let mut g = Grid::<HeatField>::from_table_data(&data);
-        let start = Position::new(0, 0).unwrap();
-        let end = Position::new(12, 12).unwrap();
+        let start = Pos::new(0, 0);
+        let end = Pos::new(12, 12);
         g.best_path(start, end);
         assert_eq!(102, g[12][12].traversal.as_ref().unwrap().total_heat_loss);
     }
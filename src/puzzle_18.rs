@@ -1,105 +1,88 @@
-use crate::util::load_file;
+use crate::util::geom::{Direction, Pos};
+use crate::util::polygon::Polygon;
+use crate::util::{checked, load_file};
 
 pub fn puzzle_18_1() -> u64 {
     let input = load_file("18/input.txt");
     let path = Path::parse(&input);
-    path.area() as u64
+    path.area()
 }
 
 pub fn puzzle_18_2() -> u64 {
     let input = load_file("18/input.txt");
     let path = Path::parse_hex(&input);
-    path.area() as u64
+    path.area()
 }
 
 #[derive(Debug, PartialEq, Eq)]
 struct Path {
-    path: Vec<(isize, isize)>,
+    path: Vec<Pos>,
     circumference: usize,
 }
 
 impl Path {
     fn parse_hex(s: &str) -> Self {
-        let mut circumference = 0usize;
-        let path = s
-            .trim()
-            .lines()
-            .map(|l| {
-                let hex = l.trim().split(" ").skip(2).next().expect("hex part");
-                (
-                    isize::from_str_radix(&hex[2..7], 16).expect("distance"),
-                    &hex[7..8],
-                )
-            })
-            .scan((0, 0), |state, (dist, dir)| {
-                circumference += dist as usize;
-                match dir {
-                    "0" => state.0 += dist,
-                    "1" => state.1 -= dist,
-                    "2" => state.0 -= dist,
-                    "3" => state.1 += dist,
-                    _ => panic!("unexpected direction"),
-                };
-                Some(*state)
-            })
-            .collect();
-
-        let mut path = Self::normalize_path(path);
-        if path[0].1 != path[1].1 {
-            path.rotate_left(1);
-        }
-        Path {
-            path,
-            circumference,
-        }
+        Self::from_steps(s.trim().lines().map(|l| {
+            let hex = l.trim().split(' ').nth(2).expect("hex part");
+            let dir = match &hex[7..8] {
+                "0" => Direction::East,
+                "1" => Direction::South,
+                "2" => Direction::West,
+                "3" => Direction::North,
+                _ => panic!("unexpected direction"),
+            };
+            (
+                dir,
+                isize::from_str_radix(&hex[2..7], 16).expect("distance"),
+            )
+        }))
     }
 
     fn parse(s: &str) -> Self {
+        Self::from_steps(s.trim().lines().map(|l| {
+            let mut it = l.trim().split(' ');
+            let dir = match it.next().expect("direction") {
+                "R" => Direction::East,
+                "D" => Direction::South,
+                "L" => Direction::West,
+                "U" => Direction::North,
+                _ => panic!("unexpected direction"),
+            };
+            let dist = it.next().expect("distance");
+            (dir, dist.parse::<isize>().expect("invalid distance"))
+        }))
+    }
+
+    fn from_steps(steps: impl Iterator<Item = (Direction, isize)>) -> Self {
         let mut circumference = 0usize;
-        let path = s
-            .trim()
-            .lines()
-            .map(|l| {
-                let mut it = l.trim().split(" ");
-                (it.next().expect("direction"), it.next().expect("distance"))
-            })
-            .scan((0, 0), |state, (dir, dist)| {
-                let dist = dist.parse::<isize>().expect("invalid distance");
-                circumference += isize::abs(dist) as usize;
-                match dir {
-                    "R" => state.0 += dist,
-                    "D" => state.1 -= dist,
-                    "L" => state.0 -= dist,
-                    "U" => state.1 += dist,
-                    _ => panic!("unexpected direction"),
-                };
-                Some(*state)
+        let path = steps
+            .scan(Pos::default(), |pos, (dir, dist)| {
+                circumference = checked::add(18, circumference, dist.unsigned_abs());
+                *pos = pos.offset(dir, dist);
+                Some(*pos)
             })
             .collect();
-        let mut path = Self::normalize_path(path);
-        if path[0].1 != path[1].1 {
-            path.rotate_left(1);
-        }
+
         Path {
-            path,
+            path: Self::normalize_path(path),
             circumference,
         }
     }
 
-    fn normalize_path(path: Vec<(isize, isize)>) -> Vec<(isize, isize)> {
-        let minx = path.iter().map(|(x, _)| *x).min().expect("minx");
-        let miny = path.iter().map(|(_, y)| *y).min().expect("miny");
+    fn normalize_path(path: Vec<Pos>) -> Vec<Pos> {
+        let minrow = path.iter().map(|p| p.row).min().expect("minrow");
+        let mincol = path.iter().map(|p| p.col).min().expect("mincol");
         path.into_iter()
-            .map(|(x, y)| ((x - minx), (y - miny)))
+            .map(|p| p - Pos::new(minrow, mincol))
             .collect()
     }
 
-    fn area(&self) -> usize {
-        let inner = self
-            .path
-            .chunks(2)
-            .fold(0, |sum, chunk| sum + (chunk[1].0 - chunk[0].0) * chunk[0].1);
-        (inner + self.circumference as isize / 2 + 1) as usize
+    /// Dug out cubes: the trench itself plus every cube it encloses
+    fn area(&self) -> u64 {
+        let points = Polygon::new(self.path.clone())
+            .lattice_points()
+            .expect("day 18: lagoon area overflows i128");
+        checked::convert(18, points)
     }
 }
 
@@ -127,20 +110,20 @@ mod test {
     fn test_parse_normalized() {
         let expected = Path {
             path: vec![
-                (6, 4),
-                (4, 4),
-                (4, 2),
-                (6, 2),
-                (6, 0),
-                (1, 0),
-                (1, 2),
-                (0, 2),
-                (0, 4),
-                (2, 4),
-                (2, 7),
-                (0, 7),
-                (0, 9),
-                (6, 9),
+                Pos::new(0, 6),
+                Pos::new(5, 6),
+                Pos::new(5, 4),
+                Pos::new(7, 4),
+                Pos::new(7, 6),
+                Pos::new(9, 6),
+                Pos::new(9, 1),
+                Pos::new(7, 1),
+                Pos::new(7, 0),
+                Pos::new(5, 0),
+                Pos::new(5, 2),
+                Pos::new(2, 2),
+                Pos::new(2, 0),
+                Pos::new(0, 0),
             ],
             circumference: 38,
         };
@@ -1,104 +1,260 @@
-use crate::util::load_lines;
+use crate::util::{checked, load_lines};
+use anyhow::{bail, Context};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
 use std::str::FromStr;
 
-const MAX_CUBES: CubeSet = CubeSet::from_tuple((12, 13, 14));
-
 pub fn puzzle_2_1() -> u32 {
-    load_lines("2/input.txt")
-        .map(|l| l.unwrap().parse::<Game>().expect("valid puzzle input"))
-        .filter(|g| g.possible(&MAX_CUBES))
-        .fold(0, |id_sum, g| id_sum + g.get_id())
+    puzzle_2_1_with(&Bag::default(), Mode::default())
 }
 
 pub fn puzzle_2_2() -> u32 {
+    puzzle_2_2_with(&Bag::default(), Mode::default())
+}
+
+pub fn puzzle_2_1_with(bag: &Bag, mode: Mode) -> u32 {
+    checked::sum(
+        2,
+        load_games(mode)
+            .filter(|g| g.possible(bag))
+            .map(|g| g.get_id()),
+    )
+}
+
+pub fn puzzle_2_2_with(bag: &Bag, mode: Mode) -> u32 {
+    checked::sum(2, load_games(mode).map(|g| g.minimal_set(bag).power()))
+}
+
+/// The most likely bags that fit within `upper`, given the rounds of all games.
+/// Fails if more than `MAX_SEARCH` bags would have to be tried.
+/// Run with `--estimate-bag`, `--bag` giving `upper`.
+pub fn estimate_bag(upper: &Bag, mode: Mode) -> anyhow::Result<BagEstimate> {
+    let games: Vec<Game> = load_games(mode).collect();
+    BagEstimate::new(&games, upper)
+}
+
+fn load_games(mode: Mode) -> impl Iterator<Item = Game> {
     load_lines("2/input.txt")
-        .map(|l| l.unwrap().parse::<Game>().expect("valid puzzle input"))
-        .map(|g| g.minimal_set().power())
-        .sum()
+        .map(move |l| Game::parse(&l.unwrap(), mode).unwrap_or_else(|e| panic!("{e}")))
 }
 
+/// A number of cubes of one colour, e.g. `3 blue`
 #[derive(Debug, PartialEq, Eq)]
-enum ColorCube {
-    Red(u32),
-    Green(u32),
-    Blue(u32),
+struct ColorCube {
+    color: String,
+    count: u32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 enum ColorCubeParseError {
     NotEnoughItems,
+    TooManyItems,
     NoNumber,
-    NoColor,
+    InvalidColor(String),
+}
+
+impl Display for ColorCubeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColorCubeParseError::NotEnoughItems => write!(f, "expected a count and a colour"),
+            ColorCubeParseError::TooManyItems => write!(f, "unexpected text after the colour"),
+            ColorCubeParseError::NoNumber => write!(f, "count is not a number"),
+            ColorCubeParseError::InvalidColor(color) => write!(f, "invalid colour {color:?}"),
+        }
+    }
+}
+
+fn is_color_name(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(char::is_alphabetic)
 }
 
 impl FromStr for ColorCube {
     type Err = ColorCubeParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use ColorCube::*;
-        let mut it = s.split(' ').map(str::trim);
+        let mut it = s.split_whitespace();
         let count = it
             .next()
             .ok_or(Self::Err::NotEnoughItems)?
             .parse::<u32>()
             .map_err(|_| Self::Err::NoNumber)?;
         let color = it.next().ok_or(Self::Err::NotEnoughItems)?;
-        Ok(match color {
-            "red" => Red(count),
-            "green" => Green(count),
-            "blue" => Blue(count),
-            _ => return Err(Self::Err::NoColor),
+        if it.next().is_some() {
+            return Err(Self::Err::TooManyItems);
+        }
+        if !is_color_name(color) {
+            return Err(Self::Err::InvalidColor(color.to_string()));
+        }
+        Ok(ColorCube {
+            color: color.to_string(),
+            count,
         })
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-struct CubeSet {
-    red: u32,
-    green: u32,
-    blue: u32,
+/// How malformed input is treated
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Mode {
+    /// Every cube must parse and each colour may appear once per round
+    #[default]
+    Strict,
+    /// Invalid cubes are skipped and repeated colours are summed
+    Lenient,
 }
 
+/// Cube counts by colour
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+struct CubeSet(BTreeMap<String, u32>);
+
 impl CubeSet {
-    const fn from_tuple(rgb: (u32, u32, u32)) -> Self {
-        CubeSet {
-            red: rgb.0,
-            green: rgb.1,
-            blue: rgb.2,
-        }
+    fn from_pairs<'a>(pairs: impl IntoIterator<Item = (&'a str, u32)>) -> Self {
+        CubeSet(
+            pairs
+                .into_iter()
+                .map(|(color, count)| (color.to_string(), count))
+                .collect(),
+        )
+    }
+
+    #[cfg(test)]
+    fn from_tuple(rgb: (u32, u32, u32)) -> Self {
+        CubeSet::from_pairs([("red", rgb.0), ("green", rgb.1), ("blue", rgb.2)])
+    }
+
+    /// Colours not in the set count as zero
+    fn get(&self, color: &str) -> u32 {
+        self.0.get(color).copied().unwrap_or(0)
     }
 
     fn possible(&self, max: &CubeSet) -> bool {
-        self.red <= max.red && self.green <= max.green && self.blue <= max.blue
+        self.0.iter().all(|(color, count)| *count <= max.get(color))
     }
 
+    /// Product of the counts of every colour in the set
     fn power(&self) -> u32 {
-        self.red * self.green * self.blue
+        self.0
+            .values()
+            .fold(1, |power, count| checked::mul(2, power, *count))
+    }
+}
+
+/// The cubes in the bag; colours outside it count as zero cubes
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Bag {
+    limits: CubeSet,
+}
+
+impl Default for Bag {
+    fn default() -> Self {
+        Bag {
+            limits: CubeSet::from_pairs([("red", 12), ("green", 13), ("blue", 14)]),
+        }
+    }
+}
+
+impl Bag {
+    fn colors(&self) -> impl Iterator<Item = &str> {
+        self.limits.0.keys().map(String::as_str)
+    }
+}
+
+/// As parsed by `FromStr`
+impl Display for Bag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pairs: Vec<String> = self
+            .limits
+            .0
+            .iter()
+            .map(|(color, count)| format!("{color}={count}"))
+            .collect();
+        write!(f, "{}", pairs.join(","))
+    }
+}
+
+/// Comma separated `colour=count` pairs, e.g. `red=12,green=13,blue=14,yellow=5`
+impl FromStr for Bag {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut limits = CubeSet::default();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let Some((color, count)) = part.split_once('=') else {
+                bail!("expected `colour=count`, got {part:?}");
+            };
+            let color = color.trim();
+            if !is_color_name(color) {
+                bail!("invalid colour {color:?}");
+            }
+            let count = count
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid count for {color}"))?;
+            if limits.0.insert(color.to_string(), count).is_some() {
+                bail!("colour {color} given twice");
+            }
+        }
+        if limits.0.is_empty() {
+            bail!("the bag has no colours");
+        }
+        Ok(Bag { limits })
     }
 }
 
-#[derive(Debug)]
-struct GameRoundParseError {}
+#[derive(Debug, PartialEq, Eq)]
+enum GameRoundParseError {
+    InvalidCube {
+        token: String,
+        error: ColorCubeParseError,
+    },
+    DuplicateColor {
+        token: String,
+    },
+}
+
+impl Display for GameRoundParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameRoundParseError::InvalidCube { token, error } => {
+                write!(f, "invalid cubes {token:?}: {error}")
+            }
+            GameRoundParseError::DuplicateColor { token } => {
+                write!(f, "colour repeated in {token:?}")
+            }
+        }
+    }
+}
+
+impl CubeSet {
+    /// A round such as `3 blue, 4 red`
+    fn parse(s: &str, mode: Mode) -> Result<Self, GameRoundParseError> {
+        let mut set = CubeSet::default();
+        for token in s.split(',').map(str::trim) {
+            let cube = match (token.parse::<ColorCube>(), mode) {
+                (Ok(cube), _) => cube,
+                (Err(_), Mode::Lenient) => continue,
+                (Err(error), Mode::Strict) => {
+                    return Err(GameRoundParseError::InvalidCube {
+                        token: token.to_string(),
+                        error,
+                    })
+                }
+            };
+            if mode == Mode::Strict && set.0.contains_key(&cube.color) {
+                return Err(GameRoundParseError::DuplicateColor {
+                    token: token.to_string(),
+                });
+            }
+            *set.0.entry(cube.color).or_insert(0) += cube.count;
+        }
+        Ok(set)
+    }
+}
 
 impl FromStr for CubeSet {
     type Err = GameRoundParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use ColorCube::*;
-        let rgb = s
-            .split(',')
-            .map(str::trim)
-            .map(|s| s.parse::<ColorCube>())
-            .fold((0, 0, 0), |mut rgb, cube| {
-                match cube {
-                    Ok(Red(x)) => rgb.0 += x,
-                    Ok(Green(x)) => rgb.1 += x,
-                    Ok(Blue(x)) => rgb.2 += x,
-                    _ => (), // Invalid cubes are ignored...
-                };
-                rgb
-            });
-        Ok(CubeSet::from_tuple(rgb))
+        CubeSet::parse(s, Mode::default())
     }
 }
 
@@ -108,76 +264,329 @@ struct Game {
     rounds: Vec<CubeSet>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 enum GameParseError {
     NoGameId,
-    InvalidGameId,
-    NoRounds,
-    InvalidGameRound,
+    InvalidGameId(String),
+    NoRounds(u32),
+    /// `round` is 1-based
+    InvalidGameRound {
+        game: u32,
+        round: usize,
+        error: GameRoundParseError,
+    },
+}
+
+impl Display for GameParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameParseError::NoGameId => write!(f, "missing game id"),
+            GameParseError::InvalidGameId(id) => write!(f, "invalid game id {id:?}"),
+            GameParseError::NoRounds(game) => write!(f, "game {game}: missing rounds"),
+            GameParseError::InvalidGameRound { game, round, error } => {
+                write!(f, "game {game}, round {round}: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameParseError {}
+
+impl Game {
+    fn parse(s: &str, mode: Mode) -> Result<Self, GameParseError> {
+        let (head, rounds) = match s.split_once(':') {
+            Some((head, rounds)) => (head, Some(rounds)),
+            None => (s, None),
+        };
+        let id_token = head
+            .split(' ')
+            .next_back()
+            .ok_or(GameParseError::NoGameId)?;
+        let id = id_token
+            .parse::<u32>()
+            .map_err(|_| GameParseError::InvalidGameId(id_token.to_string()))?;
+        let rounds = rounds
+            .ok_or(GameParseError::NoRounds(id))?
+            .split(';')
+            .enumerate()
+            .map(|(i, round)| {
+                CubeSet::parse(round, mode).map_err(|error| GameParseError::InvalidGameRound {
+                    game: id,
+                    round: i + 1,
+                    error,
+                })
+            })
+            .collect::<Result<Vec<CubeSet>, _>>()?;
+        Ok(Game { id, rounds })
+    }
 }
 
 impl FromStr for Game {
     type Err = GameParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut it = s.split(':').map(str::trim);
-        let id = it
-            .next()
-            .ok_or(Self::Err::NoGameId)?
-            .split(" ")
-            .last()
-            .ok_or(Self::Err::NoGameId)?
-            .parse::<u32>()
-            .or(Err(Self::Err::InvalidGameId))?;
-        let rounds = it
-            .next()
-            .ok_or(Self::Err::NoRounds)?
-            .split(";")
-            .map(|s| s.parse::<CubeSet>())
-            .collect::<Result<Vec<CubeSet>, _>>()
-            .or(Err(Self::Err::InvalidGameRound))?;
-        Ok(Game { id, rounds })
+        Game::parse(s, Mode::default())
     }
 }
 
 impl Game {
-    fn possible(&self, max: &CubeSet) -> bool {
-        self.rounds.iter().fold(true, |p, r| p && r.possible(max))
+    fn possible(&self, bag: &Bag) -> bool {
+        self.rounds.iter().all(|r| r.possible(&bag.limits))
     }
 
     fn get_id(&self) -> u32 {
         self.id
     }
 
-    fn minimal_set(&self) -> CubeSet {
-        CubeSet::from_tuple(self.rounds.iter().fold((0, 0, 0), |max, r| {
-            (r.red.max(max.0), r.green.max(max.1), r.blue.max(max.2))
+    /// Fewest cubes of every colour in the bag or drawn in the game that make all rounds possible
+    fn minimal_set(&self, bag: &Bag) -> CubeSet {
+        let drawn = self
+            .rounds
+            .iter()
+            .flat_map(|r| r.0.keys().map(String::as_str));
+        let colors: BTreeSet<&str> = bag.colors().chain(drawn).collect();
+        CubeSet::from_pairs(colors.into_iter().map(|color| {
+            (
+                color,
+                self.rounds.iter().map(|r| r.get(color)).max().unwrap_or(0),
+            )
         }))
     }
 }
 
+/// ln of the binomial coefficient; minus infinity if `k > n`
+fn ln_choose(n: u32, k: u32) -> f64 {
+    if k > n {
+        return f64::NEG_INFINITY;
+    }
+    let k = k.min(n - k);
+    (0..k).map(|i| ((n - i) as f64 / (k - i) as f64).ln()).sum()
+}
+
+/// Maximum-likelihood inference of the bag contents.
+///
+/// Each round is taken to be one draw without replacement from the full bag, so
+/// its likelihood is multivariate hypergeometric: the product over colours of
+/// `C(n_c, k_c)`, divided by `C(N, k)`. Rounds are independent.
+#[derive(Debug, Clone)]
+pub struct BagEstimate {
+    colors: Vec<String>,
+    /// Per colour, how many rounds drew each number of cubes of it
+    drawn: Vec<BTreeMap<u32, u32>>,
+    /// How many rounds drew each total number of cubes
+    totals: BTreeMap<u32, u32>,
+    upper: Bag,
+    /// The most likely bags with their log-likelihoods, best first
+    pub ranked: Vec<(Bag, f64)>,
+    /// How many bags between the observed maximum and the upper limit were tried
+    pub searched: usize,
+}
+
+impl BagEstimate {
+    fn new(games: &[Game], upper: &Bag) -> anyhow::Result<Self> {
+        let colors: Vec<String> = upper.colors().map(String::from).collect();
+        let mut drawn = vec![BTreeMap::new(); colors.len()];
+        let mut totals = BTreeMap::new();
+        for round in games.iter().flat_map(|g| g.rounds.iter()) {
+            for (i, color) in colors.iter().enumerate() {
+                *drawn[i].entry(round.get(color)).or_insert(0) += 1;
+            }
+            *totals.entry(round.0.values().sum()).or_insert(0) += 1;
+        }
+        let mut estimate = BagEstimate {
+            colors,
+            drawn,
+            totals,
+            upper: upper.clone(),
+            ranked: vec![],
+            searched: 0,
+        };
+        // Only colours in `upper` are searched, and no bag can draw any other
+        if games.iter().all(|g| g.possible(upper)) {
+            estimate.search(upper)?;
+        }
+        Ok(estimate)
+    }
+
+    /// Log-likelihood of the observed rounds if the bag held `counts` of each colour
+    fn ln_likelihood_of(&self, counts: &[u32]) -> f64 {
+        let per_color: f64 = self
+            .drawn
+            .iter()
+            .zip(counts)
+            .map(|(drawn, n)| ln_likelihood_term(drawn, *n))
+            .sum();
+        per_color - ln_likelihood_term(&self.totals, counts.iter().sum())
+    }
+
+    /// Log-likelihood of `bag`, ranked or not; minus infinity if it cannot produce some round
+    pub fn ln_likelihood(&self, bag: &Bag) -> f64 {
+        let counts: Vec<u32> = self.colors.iter().map(|c| bag.limits.get(c)).collect();
+        self.ln_likelihood_of(&counts)
+    }
+
+    /// The most likely bag and its log-likelihood
+    pub fn best(&self) -> Option<&(Bag, f64)> {
+        self.ranked.first()
+    }
+
+    /// Likelihood of `bag` as a fraction of the best candidate's
+    pub fn relative_likelihood(&self, bag: &Bag) -> f64 {
+        match self.best() {
+            Some((_, best)) => (self.ln_likelihood(bag) - best).exp(),
+            None => 0.0,
+        }
+    }
+
+    /// Colours of the best bag at the search limit. The likelihood may
+    /// still be rising there, so a larger bag could be more likely.
+    pub fn at_limit(&self) -> Vec<&str> {
+        let Some((best, _)) = self.best() else {
+            return vec![];
+        };
+        self.upper
+            .colors()
+            .filter(|c| best.limits.get(c) == self.upper.limits.get(c))
+            .collect()
+    }
+
+    // Every term only depends on one colour count or on the total,
+    // so they are tabulated before going through all the combinations
+    fn search(&mut self, upper: &Bag) -> anyhow::Result<()> {
+        let ranges: Vec<(u32, u32)> = self
+            .drawn
+            .iter()
+            .zip(self.colors.iter())
+            .map(|(drawn, color)| {
+                let lower = drawn.keys().max().copied().unwrap_or(0);
+                (lower, upper.limits.get(color))
+            })
+            .collect();
+        if ranges.iter().any(|(lower, upper)| lower > upper) {
+            return Ok(());
+        }
+        let size = ranges.iter().try_fold(1u64, |size, (lower, upper)| {
+            size.checked_mul(u64::from(upper - lower) + 1)
+        });
+        if size.is_none_or(|size| size > MAX_SEARCH) {
+            bail!("more than {MAX_SEARCH} bags fit within {upper}; lower the limits");
+        }
+        let Some(max_total) = ranges
+            .iter()
+            .try_fold(0u32, |total, (_, upper)| total.checked_add(*upper))
+        else {
+            bail!(
+                "the limits of {upper} add up to more than {} cubes",
+                u32::MAX
+            );
+        };
+        let per_color: Vec<Vec<f64>> = ranges
+            .iter()
+            .zip(self.drawn.iter())
+            .map(|((lower, upper), drawn)| {
+                (*lower..=*upper)
+                    .map(|n| ln_likelihood_term(drawn, n))
+                    .collect()
+            })
+            .collect();
+        let min_total: u32 = ranges.iter().map(|(lower, _)| lower).sum();
+        let totals: Vec<f64> = (min_total..=max_total)
+            .map(|n| ln_likelihood_term(&self.totals, n))
+            .collect();
+
+        // Only the counts of the best few are kept; ties keep the one found first
+        let mut top: Vec<(Vec<u32>, f64)> = Vec::with_capacity(RANKED + 1);
+        let mut counts: Vec<u32> = ranges.iter().map(|(lower, _)| *lower).collect();
+        loop {
+            let ln_likelihood = counts
+                .iter()
+                .zip(ranges.iter())
+                .zip(per_color.iter())
+                .map(|((n, (lower, _)), terms)| terms[(n - lower) as usize])
+                .sum::<f64>()
+                - totals[(counts.iter().sum::<u32>() - min_total) as usize];
+            self.searched += 1;
+            if top.len() < RANKED || top[top.len() - 1].1 < ln_likelihood {
+                let at = top.partition_point(|(_, ln)| ln.total_cmp(&ln_likelihood).is_ge());
+                top.insert(at, (counts.clone(), ln_likelihood));
+                top.truncate(RANKED);
+            }
+            // Next combination, counting up like an odometer
+            let Some(i) = (0..counts.len()).find(|&i| counts[i] < ranges[i].1) else {
+                break;
+            };
+            counts[i] += 1;
+            for (j, count) in counts.iter_mut().enumerate().take(i) {
+                *count = ranges[j].0;
+            }
+        }
+        self.ranked = top
+            .into_iter()
+            .map(|(counts, ln_likelihood)| {
+                let limits = CubeSet::from_pairs(
+                    self.colors
+                        .iter()
+                        .map(String::as_str)
+                        .zip(counts.iter().copied()),
+                );
+                (Bag { limits }, ln_likelihood)
+            })
+            .collect();
+        Ok(())
+    }
+}
+
+/// How many of the most likely bags an estimate keeps
+pub const RANKED: usize = 5;
+
+/// How many bags an estimate tries at most
+pub const MAX_SEARCH: u64 = 10_000_000;
+
+/// Sum of `ln C(n, k)` over the rounds drawing `k`
+fn ln_likelihood_term(drawn: &BTreeMap<u32, u32>, n: u32) -> f64 {
+    drawn
+        .iter()
+        .map(|(k, rounds)| *rounds as f64 * ln_choose(n, *k))
+        .sum()
+}
+
+/// The best bag, the proposed one and the runners-up
+impl Display for BagEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some((best, ln_likelihood)) = self.best() else {
+            return write!(f, "no candidate bag can produce every round");
+        };
+        writeln!(f, "most likely bag: {best} (ln L = {ln_likelihood:.3})")?;
+        for (bag, ln) in self.ranked.iter().skip(1) {
+            writeln!(f, "  {bag}: {:.3e} relative", (ln - ln_likelihood).exp())?;
+        }
+        let at_limit = self.at_limit();
+        if !at_limit.is_empty() {
+            writeln!(f, "at the search limit: {}", at_limit.join(", "))?;
+        }
+        write!(f, "{} candidates", self.searched)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
     #[test]
     fn test_parse_color_cube() {
-        assert_eq!(ColorCube::Red(1), "1 red".parse::<ColorCube>().unwrap());
-        assert_eq!(
-            ColorCube::Green(34),
-            "34 green".parse::<ColorCube>().unwrap()
-        );
-        assert_eq!(ColorCube::Blue(0), "0 blue".parse::<ColorCube>().unwrap());
+        let cube = |color: &str, count| ColorCube {
+            color: color.to_string(),
+            count,
+        };
+        assert_eq!(cube("red", 1), "1 red".parse::<ColorCube>().unwrap());
+        assert_eq!(cube("green", 34), "34 green".parse::<ColorCube>().unwrap());
+        assert_eq!(cube("blue", 0), "0 blue".parse::<ColorCube>().unwrap());
     }
 
     #[test]
     fn test_parse_game_round() {
         assert_eq!(
-            CubeSet {
-                red: 1,
-                green: 2,
-                blue: 3
-            },
+            CubeSet::from_tuple((1, 2, 3)),
             "1 red, 2 green, 3 blue".parse::<CubeSet>().unwrap()
         );
     }
@@ -213,7 +622,7 @@ mod test {
     fn test_game_possible() {
         let round1 = CubeSet::from_tuple((1, 2, 3));
         let round2 = CubeSet::from_tuple((4, 5, 6));
-        let maximum = CubeSet::from_tuple((5, 5, 5));
+        let maximum: Bag = "red=5,green=5,blue=5".parse().unwrap();
         let game1 = Game {
             id: 1,
             rounds: vec![round1.clone()],
@@ -235,6 +644,164 @@ mod test {
             id: 1,
             rounds: vec![round1, round2, round3],
         };
-        assert_eq!(CubeSet::from_tuple((4, 8, 6)), game1.minimal_set());
+        assert_eq!(
+            CubeSet::from_tuple((4, 8, 6)),
+            game1.minimal_set(&Bag::default())
+        );
+        assert_eq!(192, game1.minimal_set(&Bag::default()).power());
+    }
+
+    #[test]
+    fn test_strict_errors() {
+        let err = "Game 7: 1 red; 3 bl3u, 2 green"
+            .parse::<Game>()
+            .unwrap_err();
+        assert_eq!(
+            "game 7, round 2: invalid cubes \"3 bl3u\": invalid colour \"bl3u\"",
+            err.to_string()
+        );
+        let err = "Game 3: 1 red, 2 red".parse::<Game>().unwrap_err();
+        assert_eq!(
+            GameParseError::InvalidGameRound {
+                game: 3,
+                round: 1,
+                error: GameRoundParseError::DuplicateColor {
+                    token: "2 red".to_string()
+                }
+            },
+            err
+        );
+        let err = "Game 4: 2 blue;".parse::<Game>().unwrap_err();
+        assert_eq!(
+            "game 4, round 2: invalid cubes \"\": expected a count and a colour",
+            err.to_string()
+        );
+        assert_eq!(
+            GameParseError::InvalidGameId("x".to_string()),
+            "Game x: 1 red".parse::<Game>().unwrap_err()
+        );
+        assert_eq!(
+            GameParseError::NoRounds(5),
+            "Game 5".parse::<Game>().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_lenient() {
+        let input = "Game 1: 1 red, 2 red, 3 bl3u; 4 blue";
+        let game = Game::parse(input, Mode::Lenient).unwrap();
+        assert_eq!(
+            vec![
+                CubeSet::from_pairs([("red", 3)]),
+                CubeSet::from_pairs([("blue", 4)])
+            ],
+            game.rounds
+        );
+    }
+
+    #[test]
+    fn test_more_colours() {
+        let bag: Bag = "red=12, green=13, blue=14, yellow=5".parse().unwrap();
+        let game: Game = "Game 2: 6 yellow, 1 red; 2 blue, 3 green".parse().unwrap();
+        assert!(!game.possible(&bag));
+        let minimal = game.minimal_set(&bag);
+        assert_eq!(0, minimal.get("pink"));
+        assert_eq!(6 * 3 * 2, minimal.power());
+        // Colours outside the bag still count, so the power does not depend on it
+        assert_eq!(minimal, game.minimal_set(&Bag::default()));
+
+        let game: Game = "Game 3: 5 yellow; 12 red".parse().unwrap();
+        assert!(game.possible(&bag));
+        // No yellow cubes in the default bag
+        assert!(!game.possible(&Bag::default()));
+        assert_eq!(5, game.minimal_set(&Bag::default()).get("yellow"));
+    }
+
+    #[test]
+    fn test_parse_bag() {
+        assert_eq!(Bag::default(), "red=12,green=13,blue=14".parse().unwrap());
+        assert_eq!(
+            "colour red given twice",
+            "red=1,red=2".parse::<Bag>().unwrap_err().to_string()
+        );
+        assert!("red:1".parse::<Bag>().is_err());
+        assert!("red=x".parse::<Bag>().is_err());
+        assert!("".parse::<Bag>().is_err());
+    }
+
+    const EXAMPLE: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn test_ln_choose() {
+        assert_eq!(0.0, ln_choose(5, 0));
+        assert!((ln_choose(5, 2) - 10f64.ln()).abs() < 1e-12);
+        assert!((ln_choose(20, 17) - 1140f64.ln()).abs() < 1e-12);
+        assert_eq!(f64::NEG_INFINITY, ln_choose(2, 3));
+    }
+
+    #[test]
+    fn test_estimate_bag() {
+        let upper: Bag = "red=24,green=16,blue=18".parse().unwrap();
+        let games: Vec<Game> = EXAMPLE.lines().map(|l| l.parse().unwrap()).collect();
+        let estimate = BagEstimate::new(&games, &upper).unwrap();
+        // From the observed maxima up to the limits
+        assert_eq!(5 * 4 * 4, estimate.searched);
+        assert_eq!(RANKED, estimate.ranked.len());
+        assert!(estimate.ranked.windows(2).all(|w| w[0].1 >= w[1].1));
+        let (best, ln_likelihood) = estimate.best().unwrap();
+        // Same as ranking every candidate
+        let mut all: Vec<f64> = (20..=24)
+            .flat_map(|r| (13..=16).flat_map(move |g| (15..=18).map(move |b| (r, g, b))))
+            .map(|rgb| {
+                estimate.ln_likelihood(&Bag {
+                    limits: CubeSet::from_tuple(rgb),
+                })
+            })
+            .collect();
+        all.sort_by(|a, b| b.total_cmp(a));
+        let ranked: Vec<f64> = estimate.ranked.iter().map(|(_, ln)| *ln).collect();
+        assert_eq!(all[..RANKED], ranked[..]);
+        assert_eq!(*ln_likelihood, estimate.ln_likelihood(best));
+        assert_eq!(1.0, estimate.relative_likelihood(best));
+
+        // The puzzle's bag cannot produce game 3
+        assert_eq!(0.0, estimate.relative_likelihood(&Bag::default()));
+        let plausible: Bag = "red=20,green=13,blue=15".parse().unwrap();
+        assert!(estimate.relative_likelihood(&plausible) > 0.0);
+
+        let too_small: Bag = "red=12,green=13,blue=14".parse().unwrap();
+        let estimate = BagEstimate::new(&games, &too_small).unwrap();
+        assert!(estimate.best().is_none());
+        assert!(estimate.at_limit().is_empty());
+
+        let no_blue: Bag = "red=24,green=16".parse().unwrap();
+        assert!(BagEstimate::new(&games, &no_blue).unwrap().best().is_none());
+
+        let huge: Bag = "red=1000,green=1000,blue=1000".parse().unwrap();
+        let err = BagEstimate::new(&games, &huge).unwrap_err();
+        assert_eq!(
+            "more than 10000000 bags fit within blue=1000,green=1000,red=1000; lower the limits",
+            err.to_string()
+        );
+        let overflow = format!("red={},green=20,blue=20", u32::MAX);
+        assert!(BagEstimate::new(&games, &overflow.parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_single_round_likelihood() {
+        let games = vec!["Game 1: 1 red, 1 blue".parse::<Game>().unwrap()];
+        let upper: Bag = "red=2,blue=1".parse().unwrap();
+        let estimate = BagEstimate::new(&games, &upper).unwrap();
+        // Drawing both cubes from {red, blue} is certain, from {red, red, blue} 2 in 3
+        let certain: Bag = "red=1,blue=1".parse().unwrap();
+        assert_eq!(Some(&(certain, 0.0)), estimate.best());
+        assert_eq!(vec!["blue"], estimate.at_limit());
+        let two_red: Bag = "red=2,blue=1".parse().unwrap();
+        assert!((estimate.relative_likelihood(&two_red) - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!("blue=1,red=2", two_red.to_string());
     }
 }
@@ -1,7 +1,12 @@
 #![allow(unused)]
 
-use crate::util::load_file;
+use crate::util::parse::{adjacency, label, lines, parse_all, PResult};
+use crate::util::{load_file, Interner};
 use anyhow::Error;
+use nom::character::complete::{multispace0, one_of, space0};
+use nom::combinator::opt;
+use nom::sequence::{pair, preceded};
+use std::collections::HashMap;
 
 pub fn puzzle_20_1() -> u64 {
     let input = load_file("20/input.txt");
@@ -9,12 +14,13 @@ pub fn puzzle_20_1() -> u64 {
     total_pulses(&mut machines, 1000) as u64
 }
 
-type Key = String;
+/// Interned machine label
+type Key = u32;
 type Level = bool;
 const LOW: Level = true;
 const HIGH: Level = false;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 struct Pulse {
     level: Level,
     source: Key,
@@ -22,39 +28,35 @@ struct Pulse {
 }
 
 impl Pulse {
-    fn low<K>(source: K, target: K) -> Pulse
-    where
-        K: AsRef<str>,
-    {
+    fn low(source: Key, target: Key) -> Pulse {
         Pulse {
             level: LOW,
-            source: source.as_ref().into(),
-            target: target.as_ref().into(),
+            source,
+            target,
         }
     }
-    fn high<K>(source: K, target: K) -> Pulse
-    where
-        K: AsRef<str>,
-    {
+    fn high(source: Key, target: Key) -> Pulse {
         Pulse {
             level: HIGH,
-            source: source.as_ref().into(),
-            target: target.as_ref().into(),
+            source,
+            target,
         }
     }
 }
 
 trait Machine {
-    fn write_pulse(&mut self, pulse: &Pulse);
+    /// False if the pulse comes from a machine that is not connected as an input
+    fn write_pulse(&mut self, pulse: &Pulse) -> bool;
 
     fn read_pulse(&mut self) -> Vec<Pulse> {
         if let Some(level) = self.read_level() {
+            let source = self.get_key();
             self.get_outputs()
                 .iter()
-                .map(|o| Pulse {
+                .map(|&target| Pulse {
                     level,
-                    source: self.get_key(),
-                    target: o.clone(),
+                    source,
+                    target,
                 })
                 .collect()
         } else {
@@ -62,38 +64,51 @@ trait Machine {
         }
     }
 
-    fn get_outputs(&self) -> Vec<Key>;
+    fn get_outputs(&self) -> &[Key];
 
     fn read_level(&mut self) -> Option<Level>;
 
     fn get_key(&self) -> Key;
 }
 
+/// Machines indexed by their interned key; labels are only kept for parsing and display
 struct Machines {
-    index: std::collections::HashMap<Key, usize>,
-    machines: Vec<Box<dyn Machine>>,
+    labels: Interner,
+    button: Key,
+    broadcaster: Key,
+    machines: Vec<Option<Box<dyn Machine>>>,
 }
 
 impl Machines {
     fn new() -> Machines {
+        let mut labels = Interner::new();
+        let button = labels.intern("button");
+        let broadcaster = labels.intern("broadcaster");
         Machines {
-            index: std::collections::HashMap::new(),
+            labels,
+            button,
+            broadcaster,
             machines: vec![],
         }
     }
-    fn insert(&mut self, key: Key, machine: Box<dyn Machine>) {
-        self.machines.push(machine);
-        self.index.insert(key, self.machines.len() - 1);
+    fn intern(&mut self, label: &str) -> Key {
+        self.labels.intern(label)
+    }
+    fn insert(&mut self, machine: Box<dyn Machine>) {
+        let idx = machine.get_key() as usize;
+        if self.machines.len() <= idx {
+            self.machines.resize_with(idx + 1, || None);
+        }
+        self.machines[idx] = Some(machine);
     }
     fn len(&self) -> usize {
-        self.index.len()
+        self.machines.iter().flatten().count()
     }
-    fn get_mut(&mut self, key: &Key) -> Option<&mut Box<dyn Machine>> {
-        let idx = self.index.get(key)?;
-        Some(&mut self.machines[*idx])
+    fn get_mut(&mut self, key: Key) -> Option<&mut Box<dyn Machine>> {
+        self.machines.get_mut(key as usize)?.as_mut()
     }
     fn values_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn Machine>> {
-        self.machines.iter_mut()
+        self.machines.iter_mut().flatten()
     }
 }
 
@@ -107,29 +122,23 @@ struct Broadcaster {
 }
 
 impl Broadcaster {
-    fn new<S>(key: S, outputs: &[S]) -> Broadcaster
-    where
-        S: AsRef<str>,
-    {
-        let outputs = outputs
-            .into_iter()
-            .map(|o| o.as_ref().to_string())
-            .collect();
+    fn new(key: Key, outputs: &[Key]) -> Broadcaster {
         Broadcaster {
-            key: key.as_ref().to_string(),
+            key,
             level: None,
-            outputs,
+            outputs: outputs.to_vec(),
         }
     }
 }
 
 impl Machine for Broadcaster {
-    fn write_pulse(&mut self, pulse: &Pulse) {
+    fn write_pulse(&mut self, pulse: &Pulse) -> bool {
         self.level = Some(pulse.level);
+        true
     }
 
-    fn get_outputs(&self) -> Vec<Key> {
-        self.outputs.iter().cloned().collect()
+    fn get_outputs(&self) -> &[Key] {
+        &self.outputs
     }
 
     fn read_level(&mut self) -> Option<Level> {
@@ -139,7 +148,7 @@ impl Machine for Broadcaster {
     }
 
     fn get_key(&self) -> Key {
-        self.key.clone()
+        self.key
     }
 }
 
@@ -153,33 +162,27 @@ struct FlipFlop {
 }
 
 impl FlipFlop {
-    fn new<S>(key: S, outputs: &[S]) -> FlipFlop
-    where
-        S: AsRef<str>,
-    {
-        let outputs = outputs
-            .into_iter()
-            .map(|o| o.as_ref().to_string())
-            .collect();
+    fn new(key: Key, outputs: &[Key]) -> FlipFlop {
         FlipFlop {
-            key: key.as_ref().to_string(),
+            key,
             state: LOW,
             active: false,
-            outputs,
+            outputs: outputs.to_vec(),
         }
     }
 }
 
 impl Machine for FlipFlop {
-    fn write_pulse(&mut self, pulse: &Pulse) {
+    fn write_pulse(&mut self, pulse: &Pulse) -> bool {
         if pulse.level == LOW {
             self.state = !self.state;
             self.active = true
         }
+        true
     }
 
-    fn get_outputs(&self) -> Vec<Key> {
-        self.outputs.iter().cloned().collect()
+    fn get_outputs(&self) -> &[Key] {
+        &self.outputs
     }
 
     fn read_level(&mut self) -> Option<Level> {
@@ -192,7 +195,7 @@ impl Machine for FlipFlop {
     }
 
     fn get_key(&self) -> Key {
-        self.key.clone()
+        self.key
     }
 }
 
@@ -200,44 +203,34 @@ impl Machine for FlipFlop {
 #[derive(Debug)]
 struct Conjunction {
     key: Key,
-    state: std::collections::HashMap<Key, Level>,
+    state: HashMap<Key, Level>,
     active: bool,
     outputs: Vec<Key>,
 }
 
 impl Conjunction {
-    fn new<S>(key: S, inputs: &[S], outputs: &[S]) -> Conjunction
-    where
-        S: AsRef<str>,
-    {
-        let outputs = outputs
-            .into_iter()
-            .map(|o| o.as_ref().to_string())
-            .collect();
-        let state = inputs
-            .into_iter()
-            .map(|o| (o.as_ref().to_string(), LOW))
-            .collect();
+    fn new(key: Key, inputs: &[Key], outputs: &[Key]) -> Conjunction {
         Conjunction {
-            key: key.as_ref().to_string(),
-            state,
+            key,
+            state: inputs.iter().map(|&i| (i, LOW)).collect(),
             active: false,
-            outputs,
+            outputs: outputs.to_vec(),
         }
     }
 }
 
 impl Machine for Conjunction {
-    fn write_pulse(&mut self, pulse: &Pulse) {
-        *self
-            .state
-            .get_mut(&pulse.source)
-            .expect(&format!("connected input: {}", pulse.target)) = pulse.level;
+    fn write_pulse(&mut self, pulse: &Pulse) -> bool {
+        let Some(state) = self.state.get_mut(&pulse.source) else {
+            return false;
+        };
+        *state = pulse.level;
         self.active = true;
+        true
     }
 
-    fn get_outputs(&self) -> Vec<Key> {
-        self.outputs.iter().cloned().collect()
+    fn get_outputs(&self) -> &[Key] {
+        &self.outputs
     }
 
     fn read_level(&mut self) -> Option<Level> {
@@ -254,22 +247,29 @@ impl Machine for Conjunction {
     }
 
     fn get_key(&self) -> Key {
-        self.key.clone()
+        self.key
     }
 }
 
 fn total_pulses(machines: &mut Machines, inputs: usize) -> usize {
     let mut total_low = 0usize;
     let mut total_high = 0usize;
+    let button = Pulse::low(machines.button, machines.broadcaster);
     for _ in 0..inputs {
-        let mut pulses = vec![Pulse::low("button", "broadcaster")];
+        let mut pulses = vec![button];
         while !pulses.is_empty() {
             total_low += pulses.iter().filter(|p| p.level == LOW).count();
             total_high += pulses.iter().filter(|p| p.level == HIGH).count();
             let mut next_pulses = vec![];
             for p in pulses.iter() {
-                if let Some(m) = machines.get_mut(&p.target) {
-                    m.write_pulse(p);
+                if let Some(m) = machines.get_mut(p.target) {
+                    if !m.write_pulse(p) {
+                        panic!(
+                            "{} is not a connected input of {}",
+                            machines.labels.label(p.source),
+                            machines.labels.label(p.target)
+                        );
+                    }
                     next_pulses.extend(m.read_pulse());
                 }
             }
@@ -286,51 +286,52 @@ enum MachineType {
     Conjunction,
 }
 
+// A machine name with its optional type prefix
+fn machine_name(input: &str) -> PResult<'_, (Option<char>, &str)> {
+    pair(opt(one_of("%&")), label)(input)
+}
+
 fn parse_machines(input: &str) -> Result<Machines, Error> {
-    let io: Result<Vec<(&str, &str)>, _> = input
-        .trim()
-        .lines()
-        .map(|l| l.trim().split_once("->").ok_or(Error::msg("no io arrow")))
-        .collect();
+    let io = parse_all(
+        input,
+        preceded(
+            multispace0,
+            lines(preceded(space0, adjacency(machine_name))),
+        ),
+    )?;
 
-    let io: Vec<(MachineType, Key, Vec<Key>)> = io?
+    let mut machines = Machines::new();
+
+    let io: Vec<(MachineType, Key, Vec<Key>)> = io
         .into_iter()
-        .map(|(i, o)| {
+        .map(|((prefix, name), outputs)| {
             (
-                match i.trim().chars().next().unwrap() {
-                    '%' => MachineType::FlipFlip,
-                    '&' => MachineType::Conjunction,
+                match prefix {
+                    Some('%') => MachineType::FlipFlip,
+                    Some('&') => MachineType::Conjunction,
                     _ => MachineType::Broadcaster,
                 },
-                String::from(&i.trim()[..].replace("%", "").replace("&", "")),
-                o.trim()
-                    .split(",")
-                    .map(|k| String::from(k.trim()))
-                    .collect(),
+                machines.intern(name),
+                outputs.into_iter().map(|o| machines.intern(o)).collect(),
             )
         })
         .collect();
 
-    let mut machines = Machines::new();
-
-    for m in io.iter() {
-        match m.0 {
+    for (kind, key, outputs) in io.iter() {
+        match kind {
             MachineType::Broadcaster => {
-                machines.insert(m.1.clone(), Box::new(Broadcaster::new(m.1.clone(), &m.2)));
+                machines.insert(Box::new(Broadcaster::new(*key, outputs)));
             }
             MachineType::FlipFlip => {
-                machines.insert(m.1.clone(), Box::new(FlipFlop::new(m.1.clone(), &m.2)));
+                machines.insert(Box::new(FlipFlop::new(*key, outputs)));
             }
             MachineType::Conjunction => {
                 let inputs: Vec<Key> = io
                     .iter()
-                    .filter(|item| item.2.contains(&m.1))
-                    .map(|item| item.1.clone())
+                    .filter(|item| item.2.contains(key))
+                    .map(|item| item.1)
                     .collect();
-                machines.insert(
-                    m.1.clone(),
-                    Box::new(Conjunction::new(m.1.clone(), &inputs, &m.2)),
-                );
+                machines.insert(Box::new(Conjunction::new(*key, &inputs, outputs)));
             }
         }
     }
@@ -344,14 +345,14 @@ mod test {
 
     #[test]
     fn test_broadcaster() {
-        let mut bc = Broadcaster::new("broadcaster", &["ab", "cd"]);
+        let mut labels = Interner::new();
+        let [button, bc_key, ab, cd] =
+            ["button", "broadcaster", "ab", "cd"].map(|l| labels.intern(l));
+        let mut bc = Broadcaster::new(bc_key, &[ab, cd]);
         assert_eq!(Vec::<Pulse>::new(), bc.read_pulse());
-        bc.write_pulse(&Pulse::low("broadcaster", "button"));
+        bc.write_pulse(&Pulse::low(button, bc_key));
         assert_eq!(
-            vec![
-                Pulse::low("broadcaster", "ab"),
-                Pulse::low("broadcaster", "cd")
-            ],
+            vec![Pulse::low(bc_key, ab), Pulse::low(bc_key, cd)],
             bc.read_pulse()
         );
         assert_eq!(Vec::<Pulse>::new(), bc.read_pulse());
@@ -359,32 +360,36 @@ mod test {
 
     #[test]
     fn test_flipflop() {
-        let mut ff = FlipFlop::new("ff", &["ab", "cd"]);
-        ff.write_pulse(&Pulse::high("button", "ff"));
+        let mut labels = Interner::new();
+        let [button, ff_key, ab, cd] = ["button", "ff", "ab", "cd"].map(|l| labels.intern(l));
+        let mut ff = FlipFlop::new(ff_key, &[ab, cd]);
+        ff.write_pulse(&Pulse::high(button, ff_key));
         assert_eq!(Vec::<Pulse>::new(), ff.read_pulse());
 
-        ff.write_pulse(&Pulse::low("button", "ff"));
+        ff.write_pulse(&Pulse::low(button, ff_key));
         assert_eq!(
-            vec![Pulse::high("ff", "ab"), Pulse::high("ff", "cd")],
+            vec![Pulse::high(ff_key, ab), Pulse::high(ff_key, cd)],
             ff.read_pulse()
         );
 
-        ff.write_pulse(&Pulse::low("button", "ff"));
+        ff.write_pulse(&Pulse::low(button, ff_key));
         assert_eq!(
-            vec![Pulse::low("ff", "ab"), Pulse::low("ff", "cd")],
+            vec![Pulse::low(ff_key, ab), Pulse::low(ff_key, cd)],
             ff.read_pulse()
         );
     }
 
     #[test]
     fn test_conjunction() {
-        let mut conj = Conjunction::new("conj", &["ab", "cd"], &["ef", "gh"]);
+        let mut labels = Interner::new();
+        let [conj_key, ab, cd, ef, gh] = ["conj", "ab", "cd", "ef", "gh"].map(|l| labels.intern(l));
+        let mut conj = Conjunction::new(conj_key, &[ab, cd], &[ef, gh]);
         assert_eq!(Vec::<Pulse>::new(), conj.read_pulse());
 
-        conj.write_pulse(&Pulse::high("ab", "conj"));
-        conj.write_pulse(&Pulse::high("cd", "conj"));
+        conj.write_pulse(&Pulse::high(ab, conj_key));
+        conj.write_pulse(&Pulse::high(cd, conj_key));
         assert_eq!(
-            vec![Pulse::low("conj", "ef"), Pulse::low("conj", "gh")],
+            vec![Pulse::low(conj_key, ef), Pulse::low(conj_key, gh)],
             conj.read_pulse()
         );
         assert_eq!(Vec::<Pulse>::new(), conj.read_pulse());
@@ -398,21 +403,29 @@ mod test {
         // %c -> inv
         // &inv -> a
         let mut machines = Machines::new();
-        machines.insert(
-            String::from("broadcaster"),
-            Box::new(Broadcaster::new("broadcaster", &["a", "b", "c"])),
-        );
-        machines.insert(String::from("a"), Box::new(FlipFlop::new("a", &["b"])));
-        machines.insert(String::from("b"), Box::new(FlipFlop::new("b", &["c"])));
-        machines.insert(String::from("c"), Box::new(FlipFlop::new("c", &["inv"])));
-        machines.insert(
-            String::from("inv"),
-            Box::new(Conjunction::new("inv", &["c"], &["a"])),
-        );
+        let [a, b, c, inv] = ["a", "b", "c", "inv"].map(|l| machines.intern(l));
+        let broadcaster = machines.broadcaster;
+        machines.insert(Box::new(Broadcaster::new(broadcaster, &[a, b, c])));
+        machines.insert(Box::new(FlipFlop::new(a, &[b])));
+        machines.insert(Box::new(FlipFlop::new(b, &[c])));
+        machines.insert(Box::new(FlipFlop::new(c, &[inv])));
+        machines.insert(Box::new(Conjunction::new(inv, &[c], &[a])));
+        assert_eq!(5, machines.len());
 
         assert_eq!(32, total_pulses(&mut machines, 1));
     }
 
+    #[test]
+    #[should_panic(expected = "broadcaster is not a connected input of inv")]
+    fn test_unconnected_input() {
+        let mut machines = Machines::new();
+        let inv = machines.intern("inv");
+        let broadcaster = machines.broadcaster;
+        machines.insert(Box::new(Broadcaster::new(broadcaster, &[inv])));
+        machines.insert(Box::new(Conjunction::new(inv, &[], &[])));
+        total_pulses(&mut machines, 1);
+    }
+
     #[test]
     fn test_parse_example() {
         let input = "
@@ -423,7 +436,7 @@ mod test {
          &inv -> a
         ";
 
-        let mut machines = parse_machines(&input).unwrap();
+        let mut machines = parse_machines(input).unwrap();
         assert_eq!(32, total_pulses(&mut machines, 1));
         assert_eq!(16 * 8, total_pulses(&mut machines, 2));
         assert_eq!(32000000, total_pulses(&mut machines, 1000));
@@ -438,7 +451,16 @@ mod test {
             %b -> con
             &con -> output
         ";
-        let mut machines = parse_machines(&input).unwrap();
+        let mut machines = parse_machines(input).unwrap();
         assert_eq!(11687500, total_pulses(&mut machines, 1000));
     }
+
+    #[test]
+    fn test_parse_error() {
+        let err = parse_machines("broadcaster -> a\n%a => b").err().unwrap();
+        assert_eq!(
+            "line 2, column 4: expected arrow\n 2 | %a => b\n   |    ^",
+            err.to_string()
+        );
+    }
 }
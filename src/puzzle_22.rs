@@ -1,4 +1,5 @@
 #![allow(unused)]
+use crate::trace;
 use crate::util::load_file;
 use anyhow::Error;
 
@@ -133,18 +134,16 @@ impl BrickStack {
                 }
             }
 
-            /*
-            println!(
+            trace!(
                 "Lowering brick {} ({:?} ~ {:?}) from {} to {} now resting on {:?} with height {}",
                 brick.id,
                 brick.start,
                 brick.end,
                 brick.bottom(),
                 contact_height,
-                self.supported.get(&brick.id).unwrap(),
+                self.supported.get(&brick.id),
                 brick.height(),
             );
-            */
 
             brick.lower_to((contact_height) as i32);
         }
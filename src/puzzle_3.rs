@@ -1,102 +1,363 @@
-#![allow(unused)]
-
-use crate::util::load_file;
-use anyhow::Result;
+use crate::debug;
+use crate::util::geom::Pos;
+use crate::util::render::Ansi;
+use crate::util::{checked, load_file};
 use nom::branch::alt;
-use nom::bytes::complete::take_while1;
 use nom::character::complete::{anychar, one_of, u32};
 use nom::multi::many1_count;
 use nom::{IResult, Offset};
 use std::collections::{BTreeMap, BTreeSet};
-use std::isize;
-
-type Schematic = BTreeMap<Position, SchematicItem>;
+use std::io::IsTerminal;
 
 pub fn puzzle_3_1() -> u32 {
-    let input = load_file("3/input.txt");
-    let width = input.find("\n").expect("at least one line");
-    let schematic = schematic(&input, width);
-    schematic
-        .iter()
-        .filter_map(|(pos, item)| item.number().map(|num| (pos, num)))
-        .filter_map(|(pos, serial)| {
-            for ref h in pos.hull() {
-                if let Some(SchematicItem::Symbol { symbol: _ }) = schematic.get(h) {
-                    return Some(serial);
-                }
-            }
-            None
-        })
-        .fold(0, |sum, serial| sum + serial)
+    let schematic = load_schematic();
+    part_numbers(&schematic).iter().sum()
 }
 
 pub fn puzzle_3_2() -> u32 {
+    let schematic = load_schematic();
+    gear_ratios(&schematic).iter().sum()
+}
+
+/// The annotated schematic is logged at debug level
+fn load_schematic() -> Schematic {
     let input = load_file("3/input.txt");
-    let width = input.find("\n").expect("at least one line");
-    let schematic = schematic(&input, width);
-    let numbers: Vec<(Position, u32)> = schematic
+    let schematic = schematic(&input);
+    debug!(
+        "{} numbers, {} next to a symbol",
+        schematic.numbers().count(),
+        part_number_spans(&schematic).len()
+    );
+    debug!(
+        "schematic:\n{}",
+        render(&schematic, &input, Highlight::detect())
+    );
+    schematic
+}
+
+/// Symbol and value of every match of `rule` in a schematic, in reading order
+pub fn apply_rule<P: Fn(char) -> bool>(input: &str, rule: &Rule<P>) -> Vec<(char, u32)> {
+    schematic(input)
+        .apply(rule)
         .iter()
-        .filter_map(|(pos, item)| item.number().map(|n| (pos.clone(), n)))
+        .map(|m| (m.symbol, m.value))
+        .collect()
+}
+
+fn part_rule() -> Rule<impl Fn(char) -> bool> {
+    Rule {
+        symbol: |_| true,
+        arity: Arity::AtLeast(1),
+        reducer: Reducer::Sum,
+    }
+}
+
+fn gear_rule() -> Rule<impl Fn(char) -> bool> {
+    Rule {
+        symbol: |c| c == '*',
+        arity: Arity::Exactly(2),
+        reducer: Reducer::Product,
+    }
+}
+
+/// Numbers next to any symbol by span
+fn part_number_spans(schematic: &Schematic) -> BTreeMap<&Span, u32> {
+    schematic
+        .apply(&part_rule())
+        .into_iter()
+        .flat_map(|m| m.numbers)
+        .collect()
+}
+
+/// Numbers next to any symbol, each counted once
+fn part_numbers(schematic: &Schematic) -> Vec<u32> {
+    part_number_spans(schematic).into_values().collect()
+}
+
+/// Products of the two numbers next to each `*` that has exactly two
+fn gear_ratios(schematic: &Schematic) -> Vec<u32> {
+    schematic
+        .apply(&gear_rule())
+        .into_iter()
+        .map(|m| m.value)
+        .collect()
+}
+
+/// How `render` marks up the schematic
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Highlight {
+    /// Part numbers green, isolated numbers red, gears bold yellow
+    Ansi,
+    /// A line under each row: `^` under part numbers, `x` under isolated ones, `G` under gears
+    Markers,
+}
+
+impl Highlight {
+    /// Colours when stderr, where the log goes, is a terminal
+    fn detect() -> Self {
+        if std::io::stderr().is_terminal() {
+            Highlight::Ansi
+        } else {
+            Highlight::Markers
+        }
+    }
+}
+
+/// What an item counts as, for highlighting
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Role {
+    PartNumber,
+    Isolated,
+    Gear,
+    Symbol,
+}
+
+impl Role {
+    fn marker(self) -> char {
+        match self {
+            Role::PartNumber => '^',
+            Role::Isolated => 'x',
+            Role::Gear => 'G',
+            Role::Symbol => ' ',
+        }
+    }
+
+    fn paint(self, text: &str) -> String {
+        match self {
+            Role::PartNumber => Ansi::Green.paint(text),
+            Role::Isolated => Ansi::Red.paint(text),
+            Role::Gear => Ansi::Yellow.paint_bold(text),
+            Role::Symbol => text.to_string(),
+        }
+    }
+}
+
+/// `input` with the items of its `schematic` highlighted
+fn render(schematic: &Schematic, input: &str, highlight: Highlight) -> String {
+    let parts = part_number_spans(schematic);
+    let gears: BTreeSet<&Span> = schematic
+        .apply(&gear_rule())
+        .into_iter()
+        .map(|m| m.span)
         .collect();
-    let mut ratios = BTreeMap::<Position, Vec<u32>>::new();
-    for (p, n) in numbers.iter() {
-        for h in p.hull() {
-            if let Some(item) = schematic.get(&h) {
-                if item.gear().is_some() {
-                    if let Some(v) = ratios.get_mut(&h) {
-                        v.push(*n);
-                    } else {
-                        ratios.insert(h.clone(), vec![*n]);
-                    }
+    let role = |span: &Span, item: &SchematicItem| match item {
+        SchematicItem::Number { .. } if parts.contains_key(span) => Role::PartNumber,
+        SchematicItem::Number { .. } => Role::Isolated,
+        _ if gears.contains(span) => Role::Gear,
+        _ => Role::Symbol,
+    };
+
+    let mut out = vec![];
+    for (row, line) in input.lines().enumerate() {
+        let mut text = String::new();
+        let mut markers = String::new();
+        let chars: Vec<char> = line.chars().collect();
+        let mut col = 0;
+        while col < chars.len() {
+            let pos = Pos::new(row as isize, col as isize);
+            let Some((span, item)) = schematic.at(pos).filter(|(span, _)| span.start == pos) else {
+                text.push(chars[col]);
+                markers.push(' ');
+                col += 1;
+                continue;
+            };
+            let role = role(span, item);
+            let end = (col + span.len).min(chars.len());
+            let cells: String = chars[col..end].iter().collect();
+            match highlight {
+                Highlight::Ansi => text.push_str(&role.paint(&cells)),
+                Highlight::Markers => {
+                    text.push_str(&cells);
+                    markers.extend(std::iter::repeat_n(role.marker(), end - col));
                 }
             }
+            col = end;
+        }
+        out.push(text);
+        if !markers.trim_end().is_empty() {
+            out.push(markers.trim_end().to_string());
         }
     }
+    out.join("\n")
+}
 
-    ratios
-        .iter()
-        .filter(|(p, v)| v.len() == 2)
-        .map(|(_, v)| v.iter().fold(1, |a, b| a * b))
-        .sum()
+/// How many numbers a symbol must have next to it
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    fn accepts(self, count: usize) -> bool {
+        match self {
+            Arity::Exactly(n) => count == n,
+            Arity::AtLeast(n) => count >= n,
+        }
+    }
+}
+
+/// Combines the numbers next to a symbol
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Reducer {
+    Product,
+    Sum,
+    Max,
+}
+
+impl Reducer {
+    fn reduce(self, numbers: impl Iterator<Item = u32>) -> u32 {
+        match self {
+            Reducer::Product => numbers.fold(1, |a, b| checked::mul(3, a, b)),
+            Reducer::Sum => checked::sum(3, numbers),
+            Reducer::Max => numbers.max().unwrap_or(0),
+        }
+    }
+}
+
+/// Symbols matching `symbol` with an `arity` of numbers next to them
+pub struct Rule<P: Fn(char) -> bool> {
+    pub symbol: P,
+    pub arity: Arity,
+    pub reducer: Reducer,
+}
+
+/// A symbol a rule applies to, with its adjacent numbers and their reduction
+#[derive(Debug, PartialEq, Eq)]
+struct RuleMatch<'a> {
+    span: &'a Span,
+    symbol: char,
+    numbers: Vec<(&'a Span, u32)>,
+    value: u32,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-struct Position {
-    line: isize,
-    column: isize,
+/// `len` cells of one line, starting at `start`
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+struct Span {
+    start: Pos,
     len: usize,
 }
 
-impl Position {
-    fn hull(&self) -> BTreeSet<Position> {
-        let mut hull = BTreeSet::<Position>::new();
-        for l in self.line - 1..self.line + 1 + 1 {
-            for c in self.column - 1..self.column + self.len as isize + 1 {
-                if l == self.line && c >= self.column && c < self.column + self.len as isize {
-                    continue;
+impl Span {
+    fn new(line: isize, column: isize, len: usize) -> Self {
+        Span {
+            start: Pos::new(line, column),
+            len,
+        }
+    }
+
+    /// One past the last column
+    fn end(&self) -> isize {
+        self.start.col + self.len as isize
+    }
+
+    fn contains(&self, pos: Pos) -> bool {
+        pos.row == self.start.row && (self.start.col..self.end()).contains(&pos.col)
+    }
+
+    /// The cells the span covers
+    fn cells(&self) -> impl Iterator<Item = Pos> {
+        let row = self.start.row;
+        (self.start.col..self.end()).map(move |col| Pos::new(row, col))
+    }
+
+    /// The cells around the span, diagonals included
+    fn hull(&self) -> BTreeSet<Pos> {
+        let mut hull = BTreeSet::new();
+        for row in self.start.row - 1..=self.start.row + 1 {
+            for col in self.start.col - 1..=self.end() {
+                let pos = Pos::new(row, col);
+                if !self.contains(pos) {
+                    hull.insert(pos);
                 }
-                hull.insert(Position {
-                    line: l,
-                    column: c,
-                    len: 1, // TODO: This should be part of the serial
-                });
             }
         }
         hull
     }
 }
 
-impl PartialOrd for Position {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
+/// Items by span, indexed by every cell they cover
+#[derive(Debug, PartialEq, Eq, Default)]
+struct Schematic {
+    items: BTreeMap<Span, SchematicItem>,
+    cells: BTreeMap<Pos, Span>,
 }
 
-impl Ord for Position {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        let a = (self.line, self.column);
-        let b = (other.line, other.column);
-        a.cmp(&b)
+impl Schematic {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&mut self, span: Span, item: SchematicItem) {
+        for pos in span.cells() {
+            self.cells.insert(pos, span);
+        }
+        self.items.insert(span, item);
+    }
+
+    fn items(&self) -> impl Iterator<Item = (&Span, &SchematicItem)> {
+        self.items.iter()
+    }
+
+    fn numbers(&self) -> impl Iterator<Item = (&Span, u32)> {
+        self.items()
+            .filter_map(|(span, item)| item.number().map(|n| (span, n)))
+    }
+
+    /// The item covering `pos`
+    fn at(&self, pos: Pos) -> Option<(&Span, &SchematicItem)> {
+        let span = self.cells.get(&pos)?;
+        self.items.get_key_value(span)
+    }
+
+    fn distinct_items(&self, cells: impl IntoIterator<Item = Pos>) -> Vec<(&Span, &SchematicItem)> {
+        let spans: BTreeSet<&Span> = cells
+            .into_iter()
+            .filter_map(|pos| self.cells.get(&pos))
+            .collect();
+        spans
+            .into_iter()
+            .filter_map(|span| self.items.get_key_value(span))
+            .collect()
+    }
+
+    /// Items covering `pos` or one of its eight neighbours
+    fn touching(&self, pos: Pos) -> Vec<(&Span, &SchematicItem)> {
+        let mut cells = Span::new(pos.row, pos.col, 1).hull();
+        cells.insert(pos);
+        self.distinct_items(cells)
+    }
+
+    /// Items next to `span`, diagonals included: the other items touching its cells
+    fn adjacent(&self, span: &Span) -> Vec<(&Span, &SchematicItem)> {
+        let mut items: Vec<(&Span, &SchematicItem)> = span
+            .cells()
+            .flat_map(|pos| self.touching(pos))
+            .filter(|(other, _)| *other != span)
+            .collect();
+        items.sort_by_key(|(other, _)| **other);
+        items.dedup_by_key(|(other, _)| **other);
+        items
+    }
+
+    /// Every symbol `rule` applies to, in reading order
+    fn apply<P: Fn(char) -> bool>(&self, rule: &Rule<P>) -> Vec<RuleMatch<'_>> {
+        self.items()
+            .filter_map(|(span, item)| {
+                let symbol = item.symbol().filter(|c| (rule.symbol)(*c))?;
+                let numbers: Vec<(&Span, u32)> = self
+                    .adjacent(span)
+                    .into_iter()
+                    .filter_map(|(span, item)| item.number().map(|n| (span, n)))
+                    .collect();
+                rule.arity.accepts(numbers.len()).then(|| RuleMatch {
+                    span,
+                    symbol,
+                    value: rule.reducer.reduce(numbers.iter().map(|(_, n)| *n)),
+                    numbers,
+                })
+            })
+            .collect()
     }
 }
 
@@ -120,12 +381,6 @@ impl SchematicItem {
             _ => None,
         }
     }
-    fn gear(&self) -> Option<()> {
-        match self {
-            SchematicItem::Symbol { symbol: '*' } => Some(()),
-            _ => None,
-        }
-    }
 }
 
 fn space(input: &str) -> IResult<&str, SchematicItem> {
@@ -143,27 +398,22 @@ fn symbol(input: &str) -> IResult<&str, SchematicItem> {
     Ok((output, SchematicItem::Symbol { symbol: c }))
 }
 
-fn schematic(input: &str, line_width: usize) -> Schematic {
-    let mut begin = input;
-    let line_width = line_width + 1; // Account for newlines
+/// The items of every line; columns count characters
+fn schematic(input: &str) -> Schematic {
     let mut schematic_map = Schematic::new();
-    loop {
-        match alt((serial, space, symbol))(begin) {
-            Ok((end, item)) => {
-                let pos = input.offset(begin) as isize;
-                let len = begin.offset(end);
-                if let SchematicItem::Space = item {
-                    // Ignoring spaces
-                } else {
-                    let line = pos / (line_width) as isize;
-                    let column = pos % (line_width) as isize;
-                    let position = Position { line, column, len };
-                    schematic_map.insert(position, item);
-                }
-                begin = end;
+    for (line, text) in input.lines().enumerate() {
+        let mut begin = text;
+        let mut column = 0;
+        while let Ok((end, item)) = alt((serial, space, symbol))(begin) {
+            let len = begin[..begin.offset(end)].chars().count();
+            if let SchematicItem::Space = item {
+                // Ignoring spaces
+            } else {
+                schematic_map.insert(Span::new(line as isize, column as isize, len), item);
             }
-            Err(_) => break,
-        };
+            column += len;
+            begin = end;
+        }
     }
     schematic_map
 }
@@ -209,129 +459,47 @@ mod test {
 
     #[test]
     fn test_parse_empty_schematic() {
-        assert_eq!(Schematic::new(), schematic("", 0));
-        assert_eq!(Schematic::new(), schematic("..........", 10));
-        assert_eq!(Schematic::new(), schematic(".....\n.....", 5));
+        assert_eq!(Schematic::new(), schematic(""));
+        assert_eq!(Schematic::new(), schematic(".........."));
+        assert_eq!(Schematic::new(), schematic(".....\n....."));
     }
 
     #[test]
-    fn test_position() {
+    fn test_span() {
         let input = "+....\n.....\n..+..\n.....\n....+\n";
         let mut expected = Schematic::new();
-        expected.insert(
-            Position {
-                line: 0,
-                column: 0,
-                len: 1,
-            },
-            SchematicItem::Symbol { symbol: '+' },
-        );
-        expected.insert(
-            Position {
-                line: 2,
-                column: 2,
-                len: 1,
-            },
-            SchematicItem::Symbol { symbol: '+' },
-        );
-        expected.insert(
-            Position {
-                line: 4,
-                column: 4,
-                len: 1,
-            },
-            SchematicItem::Symbol { symbol: '+' },
-        );
-        assert_eq!(expected, schematic(input, 5));
+        expected.insert(Span::new(0, 0, 1), SchematicItem::Symbol { symbol: '+' });
+        expected.insert(Span::new(2, 2, 1), SchematicItem::Symbol { symbol: '+' });
+        expected.insert(Span::new(4, 4, 1), SchematicItem::Symbol { symbol: '+' });
+        assert_eq!(expected, schematic(input));
     }
 
     #[test]
     fn test_parse_full_schematic() {
         let input = "..6..\n.123*\n.....\n.+.4.\n99.$.";
         let mut expected = Schematic::new();
-        expected.insert(
-            Position {
-                line: 0,
-                column: 2,
-                len: 1,
-            },
-            SchematicItem::Number { number: 6 },
-        );
-        expected.insert(
-            Position {
-                line: 1,
-                column: 1,
-                len: 3,
-            },
-            SchematicItem::Number { number: 123 },
-        );
-        expected.insert(
-            Position {
-                line: 1,
-                column: 4,
-                len: 1,
-            },
-            SchematicItem::Symbol { symbol: '*' },
-        );
-        expected.insert(
-            Position {
-                line: 3,
-                column: 1,
-                len: 1,
-            },
-            SchematicItem::Symbol { symbol: '+' },
-        );
-        expected.insert(
-            Position {
-                line: 3,
-                column: 3,
-                len: 1,
-            },
-            SchematicItem::Number { number: 4 },
-        );
-        expected.insert(
-            Position {
-                line: 4,
-                column: 0,
-                len: 2,
-            },
-            SchematicItem::Number { number: 99 },
-        );
-        expected.insert(
-            Position {
-                line: 4,
-                column: 3,
-                len: 1,
-            },
-            SchematicItem::Symbol { symbol: '$' },
-        );
-        assert_eq!(expected, schematic(input, 5));
+        expected.insert(Span::new(0, 2, 1), SchematicItem::Number { number: 6 });
+        expected.insert(Span::new(1, 1, 3), SchematicItem::Number { number: 123 });
+        expected.insert(Span::new(1, 4, 1), SchematicItem::Symbol { symbol: '*' });
+        expected.insert(Span::new(3, 1, 1), SchematicItem::Symbol { symbol: '+' });
+        expected.insert(Span::new(3, 3, 1), SchematicItem::Number { number: 4 });
+        expected.insert(Span::new(4, 0, 2), SchematicItem::Number { number: 99 });
+        expected.insert(Span::new(4, 3, 1), SchematicItem::Symbol { symbol: '$' });
+        assert_eq!(expected, schematic(input));
     }
 
     #[test]
-    fn test_position_hull_size() {
-        let pos = Position {
-            line: 0,
-            column: 0,
-            len: 1,
-        };
+    fn test_span_hull_size() {
+        let pos = Span::new(0, 0, 1);
         assert_eq!(8, pos.hull().len());
 
-        let pos = Position {
-            line: 0,
-            column: 0,
-            len: 3,
-        };
+        let pos = Span::new(0, 0, 3);
         assert_eq!(12, pos.hull().len());
     }
 
     #[test]
-    fn test_position_hull() {
-        let pos = Position {
-            line: 0,
-            column: 0,
-            len: 1,
-        };
+    fn test_span_hull() {
+        let pos = Span::new(0, 0, 1);
 
         let points = [
             (-1, -1),
@@ -346,12 +514,8 @@ mod test {
 
         let positions = points
             .iter()
-            .map(|p| Position {
-                line: p.0,
-                column: p.1,
-                len: 1,
-            })
-            .collect::<BTreeSet<Position>>();
+            .map(|p| Pos::new(p.0, p.1))
+            .collect::<BTreeSet<Pos>>();
 
         assert_eq!(positions, pos.hull());
     }
@@ -360,4 +524,170 @@ mod test {
     fn test_get_number() {
         assert_eq!(Some(123), SchematicItem::Number { number: 123 }.number());
     }
+
+    const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn test_spans() {
+        let span = Span::new(2, 3, 3);
+        assert_ne!(Span::new(2, 3, 1), span);
+        assert!(Span::new(2, 3, 1) < span);
+        assert_eq!(
+            vec![Pos::new(2, 3), Pos::new(2, 4), Pos::new(2, 5)],
+            span.cells().collect::<Vec<_>>()
+        );
+        assert!(span.contains(Pos::new(2, 5)));
+        assert!(!span.contains(Pos::new(2, 6)));
+    }
+
+    #[test]
+    fn test_index_queries() {
+        let schematic = schematic(EXAMPLE);
+        let number = |n| SchematicItem::Number { number: n };
+        assert_eq!(
+            Some((&Span::new(0, 0, 3), &number(467))),
+            schematic.at(Pos::new(0, 2))
+        );
+        assert_eq!(None, schematic.at(Pos::new(0, 3)));
+
+        // The star at (1, 3) touches 467 and 35, each once
+        let touching = schematic.touching(Pos::new(1, 3));
+        assert_eq!(
+            vec![
+                (&Span::new(0, 0, 3), &number(467)),
+                (&Span::new(1, 3, 1), &SchematicItem::Symbol { symbol: '*' }),
+                (&Span::new(2, 2, 2), &number(35)),
+            ],
+            touching
+        );
+        let adjacent = schematic.adjacent(&Span::new(1, 3, 1));
+        assert_eq!(
+            vec![
+                (&Span::new(0, 0, 3), &number(467)),
+                (&Span::new(2, 2, 2), &number(35)),
+            ],
+            adjacent
+        );
+        assert_eq!(10, schematic.numbers().count());
+        let adjacent = schematic.adjacent(&Span::new(0, 5, 3));
+        assert!(adjacent.is_empty());
+    }
+
+    #[test]
+    fn test_example() {
+        let schematic = schematic(EXAMPLE);
+        assert_eq!(4361, part_numbers(&schematic).iter().sum::<u32>());
+        assert_eq!(467835, gear_ratios(&schematic).iter().sum::<u32>());
+    }
+
+    #[test]
+    fn test_rules() {
+        let schematic = schematic(EXAMPLE);
+        let rule = Rule {
+            symbol: |c| c == '*',
+            arity: Arity::AtLeast(1),
+            reducer: Reducer::Max,
+        };
+        let matches = schematic.apply(&rule);
+        assert_eq!(3, matches.len());
+        assert_eq!(
+            RuleMatch {
+                span: &Span::new(4, 3, 1),
+                symbol: '*',
+                numbers: vec![(&Span::new(4, 0, 3), 617)],
+                value: 617
+            },
+            matches[1]
+        );
+        assert_eq!(
+            vec![467, 617, 755],
+            matches.iter().map(|m| m.value).collect::<Vec<_>>()
+        );
+
+        let rule = Rule {
+            symbol: |c| c != '*',
+            arity: Arity::Exactly(1),
+            reducer: Reducer::Sum,
+        };
+        let values: Vec<(char, u32)> = schematic
+            .apply(&rule)
+            .iter()
+            .map(|m| (m.symbol, m.value))
+            .collect();
+        assert_eq!(vec![('#', 633), ('+', 592), ('$', 664)], values);
+
+        let rule = Rule {
+            symbol: |c| c == '*',
+            arity: Arity::AtLeast(1),
+            reducer: Reducer::Sum,
+        };
+        assert_eq!(
+            vec![('*', 467 + 35), ('*', 617), ('*', 755 + 598)],
+            apply_rule(EXAMPLE, &rule)
+        );
+        assert!(Arity::AtLeast(0).accepts(0));
+        assert_eq!(1, Reducer::Product.reduce(std::iter::empty()));
+    }
+
+    #[test]
+    fn test_render_markers() {
+        let input = "467..114..\n...*......\n..35..633.\n......#...";
+        let rendered = render(&schematic(input), input, Highlight::Markers);
+        let expected = "467..114..
+^^^  xxx
+...*......
+   G
+..35..633.
+  ^^  ^^^
+......#...";
+        assert_eq!(expected, rendered);
+
+        let rendered = render(&schematic(EXAMPLE), EXAMPLE, Highlight::Markers);
+        assert_eq!(Some("   G"), rendered.lines().nth(3));
+    }
+
+    #[test]
+    fn test_render_non_ascii() {
+        let input = "1€.\n..7é.";
+        let rendered = render(&schematic(input), input, Highlight::Markers);
+        assert_eq!("1€.\n^\n..7é.\n  ^", rendered);
+
+        // Columns count characters, so markers line up after multi-byte ones
+        let input = "é€*12\n...3";
+        let schematic = schematic(input);
+        let star = (&Span::new(0, 2, 1), &SchematicItem::Symbol { symbol: '*' });
+        assert_eq!(Some(star), schematic.at(Pos::new(0, 2)));
+        let rendered = render(&schematic, input, Highlight::Markers);
+        assert_eq!("é€*12\n  G^^\n...3\n   ^", rendered);
+        let plain = render(&Schematic::new(), "€é", Highlight::Ansi);
+        assert_eq!("€é", plain);
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let input = "12*3.\n..4..\n....5";
+        let rendered = render(&schematic(input), input, Highlight::Ansi);
+        let green = |s| Ansi::Green.paint(s);
+        let expected = format!(
+            "{}*{}.\n..{}..\n....{}",
+            green("12"),
+            green("3"),
+            green("4"),
+            Ansi::Red.paint("5")
+        );
+        assert_eq!(expected, rendered);
+
+        let input = "12*3.";
+        let rendered = render(&schematic(input), input, Highlight::Ansi);
+        assert!(rendered.contains(&Ansi::Yellow.paint_bold("*")));
+    }
 }
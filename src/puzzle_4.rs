@@ -1,26 +1,29 @@
-use crate::util::load_lines;
+use crate::trace;
+use crate::util::{checked, load_lines};
 use ::std::collections::BTreeSet;
-use anyhow::Error as AnyhowError;
-use std::cell::Cell;
+use anyhow::{bail, Error as AnyhowError, Result};
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
 use std::str::FromStr;
 
-pub fn puzzle_4_1() -> u32 {
-    load_lines("4/input.txt")
-        .map(|l| Card::from_str(&l.expect("read line")).expect("valid card"))
-        .map(|c| c.score())
-        .sum()
+pub fn puzzle_4_1() -> u64 {
+    checked::sum(
+        4,
+        load_lines("4/input.txt")
+            .map(|l| Card::from_str(&l.expect("read line")).expect("valid card"))
+            .map(|c| c.score()),
+    )
 }
 
-pub fn puzzle_4_2() -> u32 {
-    let mut wins: Vec<u32> = load_lines("4/input.txt")
-        .map(|l| {
-            Card::from_str(&l.expect("read line"))
-                .expect("valid card")
-                .hits()
-        })
+pub fn puzzle_4_2() -> u64 {
+    let cards: Vec<Card> = load_lines("4/input.txt")
+        .map(|l| Card::from_str(&l.expect("read line")).expect("valid card"))
         .collect();
-    multiply_cards(&mut wins);
-    wins.iter().sum()
+    let results = Scratchcards::from_cards(&cards)
+        .results()
+        .unwrap_or_else(|e| panic!("{e}"));
+    checked::sum(4, results.iter().map(|r| r.copies))
 }
 
 #[derive(Debug, PartialEq)]
@@ -30,36 +33,170 @@ struct Card {
     scored: BTreeSet<u32>,
 }
 
-#[derive(Debug, PartialEq)]
-struct MultiCard {
-    count: Cell<u64>,
-    card: Card,
-}
-
 impl Card {
-    fn score(&self) -> u32 {
-        let hits = self.hits();
-        match hits {
-            0..=1 => hits as u32,
-            n => 2u32.pow((n - 1) as u32),
-        }
+    fn score(&self) -> u64 {
+        points(self.id, self.hits())
     }
     fn hits(&self) -> u32 {
         self.scored.intersection(&self.winning).count() as u32
     }
 }
 
-fn multiply_cards(winning: &mut [u32]) {
-    let mut count = vec![1u32; winning.len()];
-    for i in 0..winning.len() {
-        for j in (i + 1)..=(i + (winning[i] as usize)) {
-            if j >= winning.len() {
-                break;
+/// 1 point for the first match, doubled for every further one
+fn points(id: u32, matches: u32) -> u64 {
+    match matches {
+        0 => 0,
+        n => 1u64
+            .checked_shl(n - 1)
+            .unwrap_or_else(|| panic!("day 4: card {id} scores 2^{}", n - 1)),
+    }
+}
+
+/// The outcome for one card once the cascade has finished
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct CardResult {
+    id: u32,
+    matches: u32,
+    points: u64,
+    copies: u64,
+}
+
+/// A step of the cascade; all copies of a card are processed together
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Event {
+    /// Each of the `copies` of `card` won a copy of every card in `won`
+    Won {
+        card: u32,
+        copies: u64,
+        won: RangeInclusive<u32>,
+    },
+    /// `card` would win copies of cards after the last one; `missing` may go past `u32::MAX`
+    PastEnd {
+        card: u32,
+        copies: u64,
+        missing: RangeInclusive<u64>,
+    },
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Event::Won { card, copies, won } => write!(
+                f,
+                "card {card} ({copies} copies) won copies of {}..={}",
+                won.start(),
+                won.end()
+            ),
+            Event::PastEnd {
+                card,
+                copies,
+                missing,
+            } => write!(
+                f,
+                "card {card} ({copies} copies) would win copies of {}..={}, past the last card",
+                missing.start(),
+                missing.end()
+            ),
+        }
+    }
+}
+
+/// Cards by id with their match counts, in table order
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Scratchcards {
+    cards: Vec<(u32, u32)>,
+}
+
+impl Scratchcards {
+    fn from_cards(cards: &[Card]) -> Self {
+        Self::from_matches(cards.iter().map(|c| (c.id, c.hits())))
+    }
+
+    /// From `(id, matches)` pairs
+    fn from_matches(cards: impl IntoIterator<Item = (u32, u32)>) -> Self {
+        Scratchcards {
+            cards: cards.into_iter().collect(),
+        }
+    }
+
+    fn events(&self) -> Cascade<'_> {
+        Cascade {
+            cards: &self.cards,
+            copies: vec![1; self.cards.len()],
+            next: 0,
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// Every card's outcome; an error if the cascade runs past the last card
+    fn results(&self) -> Result<Vec<CardResult>> {
+        let mut events = self.events();
+        for event in events.by_ref() {
+            trace!("{event}");
+            if let Event::PastEnd { .. } = event {
+                bail!("day 4: {event}");
             }
-            count[j] += count[i];
         }
+        Ok(self
+            .cards
+            .iter()
+            .zip(events.copies)
+            .map(|(&(id, matches), copies)| CardResult {
+                id,
+                matches,
+                points: points(id, matches),
+                copies,
+            })
+            .collect())
+    }
+}
+
+/// Plays the cards in order, adding won copies as it goes
+#[derive(Debug)]
+struct Cascade<'a> {
+    cards: &'a [(u32, u32)],
+    copies: Vec<u64>,
+    next: usize,
+    queued: VecDeque<Event>,
+}
+
+impl Cascade<'_> {
+    fn play(&mut self, i: usize) {
+        let (card, matches) = self.cards[i];
+        let copies = self.copies[i];
+        let last = (i + matches as usize).min(self.cards.len() - 1);
+        for j in i + 1..=last {
+            self.copies[j] = checked::add(4, self.copies[j], copies);
+        }
+        if last > i {
+            self.queued.push_back(Event::Won {
+                card,
+                copies,
+                won: self.cards[i + 1].0..=self.cards[last].0,
+            });
+        }
+        let missing = (i + matches as usize - last) as u64;
+        if missing > 0 {
+            let end = u64::from(self.cards[last].0);
+            self.queued.push_back(Event::PastEnd {
+                card,
+                copies,
+                missing: end + 1..=end + missing,
+            });
+        }
+    }
+}
+
+impl Iterator for Cascade<'_> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        while self.queued.is_empty() && self.next < self.cards.len() {
+            self.play(self.next);
+            self.next += 1;
+        }
+        self.queued.pop_front()
     }
-    winning.clone_from_slice(&count[..]);
 }
 
 impl FromStr for Card {
@@ -119,7 +256,7 @@ mod test {
 
     #[test]
     fn test_score() {
-        let winning: BTreeSet<u32> = (0..10).into_iter().collect();
+        let winning: BTreeSet<u32> = (0..10).collect();
         let scored: BTreeSet<u32> = [11, 23, 55].into_iter().collect();
         let card = Card {
             id: 0,
@@ -137,8 +274,8 @@ mod test {
         };
         assert_eq!(2, card.score());
 
-        let winning: BTreeSet<u32> = (0..10).into_iter().collect();
-        let scored: BTreeSet<u32> = (1..5).into_iter().collect();
+        let winning: BTreeSet<u32> = (0..10).collect();
+        let scored: BTreeSet<u32> = (1..5).collect();
         let card = Card {
             id: 2,
             winning,
@@ -147,17 +284,89 @@ mod test {
         assert_eq!(8, card.score());
     }
 
+    fn example() -> Scratchcards {
+        Scratchcards::from_matches((1..).zip([4, 2, 2, 1, 0, 0]))
+    }
+
     #[test]
     fn test_multiply_cards() {
-        let mut input = [4, 2, 2, 1, 0, 0];
         //          [1, 1, 1, 1, 1, 1]
         //          [1, 2, 2, 2, 2, 1]
         //          [1, 2, 4, 4, 2, 1]
         //          [1, 2, 4, 8, 6, 1]
         //          [1, 2, 4, 8, 14, 1]
         let expected = [1, 2, 4, 8, 14, 1];
-        multiply_cards(&mut input[..]);
+        let results = example().results().unwrap();
+        assert_eq!(
+            expected[..],
+            results.iter().map(|r| r.copies).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            CardResult {
+                id: 1,
+                matches: 4,
+                points: 8,
+                copies: 1
+            },
+            results[0]
+        );
+        assert_eq!(0, results[5].points);
+    }
+
+    #[test]
+    fn test_events() {
+        let events: Vec<String> = example().events().map(|e| e.to_string()).collect();
+        assert_eq!(
+            vec![
+                "card 1 (1 copies) won copies of 2..=5",
+                "card 2 (2 copies) won copies of 3..=4",
+                "card 3 (4 copies) won copies of 4..=5",
+                "card 4 (8 copies) won copies of 5..=5",
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn test_past_end() {
+        let cards = Scratchcards::from_matches([(1, 1), (2, 3), (3, 0)]);
+        let events: Vec<Event> = cards.events().collect();
+        assert_eq!(
+            vec![
+                Event::Won {
+                    card: 1,
+                    copies: 1,
+                    won: 2..=2
+                },
+                Event::Won {
+                    card: 2,
+                    copies: 2,
+                    won: 3..=3
+                },
+                Event::PastEnd {
+                    card: 2,
+                    copies: 2,
+                    missing: 4..=5
+                },
+            ],
+            events
+        );
+        let err = cards.results().unwrap_err();
+        assert_eq!(
+            "day 4: card 2 (2 copies) would win copies of 4..=5, past the last card",
+            err.to_string()
+        );
 
-        assert_eq!(expected, input);
+        let cards = Scratchcards::from_matches([(u32::MAX - 1, 3), (u32::MAX, 0)]);
+        let err = cards.results().unwrap_err();
+        assert_eq!(
+            format!(
+                "day 4: card {} (1 copies) would win copies of {}..={}, past the last card",
+                u32::MAX - 1,
+                u32::MAX as u64 + 1,
+                u32::MAX as u64 + 2
+            ),
+            err.to_string()
+        );
     }
 }
@@ -1,3 +1,4 @@
+use crate::util::interval::{Interval, IntervalSet};
 use crate::util::load_lines;
 use anyhow::{Error, Result};
 use std::{collections::BTreeMap, str::FromStr};
@@ -20,12 +21,14 @@ pub fn puzzle_5_2() -> u64 {
     lines.next();
     let maps = parse_maps(&mut lines);
 
-    seeds
+    let seeds: IntervalSet<u64> = seeds
         .chunks(2)
-        .into_iter()
-        .map(|range| [range[0], range[0] + range[1] - 1])
-        .flat_map(|range| range.into_iter().map(|seed| remap_all(seed, &maps)))
-        .fold(u64::MAX, |min, val| u64::min(min, val))
+        .map(|range| Interval::new(range[0], range[0] + range[1]))
+        .collect();
+    maps.iter()
+        .fold(seeds, |set, map| map.get_set(&set))
+        .min()
+        .expect("at least one seed")
 }
 
 fn parse_seeds(lines: &mut impl Iterator<Item = String>) -> Result<Vec<u64>> {
@@ -64,15 +67,6 @@ fn parse_map(lines: &mut impl Iterator<Item = String>) -> Result<AgriMap> {
     Ok(map)
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-struct Range(u64, u64);
-
-impl Range {
-    fn contains(&self, val: u64) -> bool {
-        self.0 <= val && val < self.1
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 struct Remap {
     dest: u64,
@@ -82,27 +76,41 @@ struct Remap {
 
 #[derive(Debug, PartialEq)]
 struct AgriMap {
-    remaps: BTreeMap<Range, Range>,
+    remaps: BTreeMap<Interval<u64>, Interval<u64>>,
 }
 
 impl AgriMap {
     fn new() -> Self {
         AgriMap {
-            remaps: BTreeMap::<Range, Range>::new(),
+            remaps: BTreeMap::new(),
         }
     }
     fn get(&self, val: u64) -> u64 {
-        for remap in self.remaps.iter() {
-            if remap.0.contains(val) {
-                return val + remap.1 .0 - remap.0 .0;
+        for (source, dest) in self.remaps.iter() {
+            if source.contains(val) {
+                return val - source.start + dest.start;
             }
         }
         val
     }
+
+    /// Image of a whole set of values, splitting intervals at remap boundaries
+    fn get_set(&self, vals: &IntervalSet<u64>) -> IntervalSet<u64> {
+        let sources: IntervalSet<u64> = self.remaps.keys().copied().collect();
+        let mut image = vals.difference(&sources);
+        for (source, dest) in self.remaps.iter() {
+            for val in vals.iter() {
+                if let Some(hit) = val.intersection(source) {
+                    image.insert(hit.translate(source.start, dest.start));
+                }
+            }
+        }
+        image
+    }
     fn push(&mut self, remap: Remap) {
         self.remaps.insert(
-            Range(remap.source, remap.source + remap.range),
-            Range(remap.dest, remap.dest + remap.range),
+            Interval::new(remap.source, remap.source + remap.range),
+            Interval::new(remap.dest, remap.dest + remap.range),
         );
     }
 }
@@ -189,6 +197,33 @@ mod test {
         assert_eq!(map.get(98), 50);
     }
 
+    #[test]
+    fn test_remapped_set() {
+        let mut map = AgriMap::new();
+        map.push(Remap {
+            dest: 50,
+            source: 98,
+            range: 2,
+        });
+        map.push(Remap {
+            dest: 52,
+            source: 50,
+            range: 48,
+        });
+        let seeds: IntervalSet<u64> = [Interval::new(79, 93), Interval::new(40, 55)]
+            .into_iter()
+            .collect();
+        // 40..50 unmapped, 50..55 -> 52..57, 79..93 -> 81..95
+        let expected: IntervalSet<u64> = [
+            Interval::new(40, 50),
+            Interval::new(52, 57),
+            Interval::new(81, 95),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(expected, map.get_set(&seeds));
+    }
+
     #[test]
     fn test_multiple_maps() {
         let maps = vec![AgriMap::new(), AgriMap::new()];
@@ -1,6 +1,5 @@
-use crate::util::load_lines;
+use crate::util::{load_lines, Counter};
 use anyhow::Error;
-use std::collections::BTreeMap;
 
 pub fn puzzle_7_1() -> u64 {
     parse_hands(false).total_winnings()
@@ -32,7 +31,7 @@ impl HandCards {
         HandCards { cards: card_arr }
     }
 
-    fn counts_to_kind(counts: Counts) -> HandKind {
+    fn counts_to_kind(counts: &[usize]) -> HandKind {
         use HandKind::*;
         match counts {
             [5, ..] => FiveOfAKind,
@@ -46,13 +45,21 @@ impl HandCards {
     }
 
     fn kind(&self) -> HandKind {
-        let mut counter = Counter::new(&self.cards);
-        counter.apply_joker_rule(&Card::Joker);
-        Self::counts_to_kind(counter.counts())
+        let counter = apply_jokers(self.cards.iter().copied().collect());
+        Self::counts_to_kind(&counter.counts())
     }
 }
 
-type Counts = [usize; 5];
+/// Jokers join whichever card is most common; a hand of only jokers stays as is
+fn apply_jokers(mut counter: Counter<Card>) -> Counter<Card> {
+    let jokers = counter.take(&Card::Joker);
+    let best = counter
+        .most_common(1)
+        .first()
+        .map_or(Card::Joker, |(card, _)| **card);
+    counter.add_n(best, jokers);
+    counter
+}
 
 #[derive(Debug, Default, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
 enum Card {
@@ -138,13 +145,13 @@ impl Hands {
 
 impl PartialOrd for Hand {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        (self.kind, self.cards).partial_cmp(&(other.kind, other.cards))
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Hand {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+        (self.kind, self.cards).cmp(&(other.kind, other.cards))
     }
 }
 
@@ -168,53 +175,11 @@ impl Hand {
     }
 }
 
-struct Counter<T>
-where
-    T: Clone + Ord,
-{
-    counts: BTreeMap<T, usize>,
-}
-
-impl<T> Counter<T>
-where
-    T: Clone + Ord,
-{
-    fn new(data: &[T]) -> Self {
-        let mut counts = BTreeMap::<T, usize>::new();
-        data.into_iter().for_each(|item| {
-            if counts.contains_key(&item) {
-                *(counts.get_mut(&item).unwrap()) += 1;
-            } else {
-                counts.insert(item.clone(), 1);
-            }
-        });
-        Counter { counts }
-    }
-
-    fn counts(&self) -> Counts {
-        let mut all_counts: Vec<usize> = self.counts.values().map(|c| *c).collect();
-        let mut counts: Counts = [0usize; 5];
-        all_counts.sort_by(|a, b| b.cmp(a));
-        counts[..all_counts.len()].copy_from_slice(&all_counts[..]);
-        counts
-    }
-
-    fn apply_joker_rule(&mut self, joker: &T) {
-        let jokers = self.counts.remove(joker).unwrap_or(0);
-        if self.counts.is_empty() {
-            self.counts.insert(joker.clone(), jokers);
-            return;
-        }
-        if let Some(max_count) = self.counts.values_mut().max() {
-            *max_count += jokers;
-        }
-    }
-}
-
 #[cfg(test)]
 mod test {
 
     use super::*;
+    use crate::trace;
 
     #[test]
     fn test_parse_hand() {
@@ -248,12 +213,21 @@ mod test {
     #[test]
     fn test_counter() {
         let cards = [Card::Six, Card::Ten, Card::King, Card::King, Card::Queen];
-        let counter = Counter::new(&cards[..]);
-        assert_eq!(1, *counter.counts.get(&Card::Six).unwrap());
-        assert_eq!(2, *counter.counts.get(&Card::King).unwrap());
+        let counter: Counter<Card> = cards.into_iter().collect();
+        assert_eq!(1, counter.get(&Card::Six));
+        assert_eq!(2, counter.get(&Card::King));
+        assert_eq!(vec![2, 1, 1, 1], counter.counts());
+    }
 
-        let expected: Counts = [2usize, 1, 1, 1, 0];
-        assert_eq!(expected, counter.counts());
+    #[test]
+    fn test_apply_jokers() {
+        let cards = [Card::Joker, Card::Ten, Card::King, Card::King, Card::Joker];
+        let counter = apply_jokers(cards.into_iter().collect());
+        assert_eq!(0, counter.get(&Card::Joker));
+        assert_eq!(4, counter.get(&Card::King));
+
+        let counter = apply_jokers([Card::Joker; 5].into_iter().collect());
+        assert_eq!(vec![5], counter.counts());
     }
 
     #[test]
@@ -289,7 +263,7 @@ mod test {
 
         assert!(hands.windows(2).all(|w| {
             let cmp = w[0] < w[1];
-            dbg!(&w[0], &w[1], cmp);
+            trace!("{:?} < {:?}: {cmp}", w[0], w[1]);
             cmp
         }));
     }
@@ -1,8 +1,6 @@
-use crate::util::load_lines;
+use crate::util::{load_lines, Interner};
 use anyhow::Error;
 use num::integer::Integer;
-use std::collections::HashMap;
-use std::iter::repeat;
 use std::str::FromStr;
 
 pub fn puzzle_8_1() -> u64 {
@@ -31,7 +29,7 @@ fn get_input() -> (Directions, Map) {
         .collect::<Result<Vec<Path>, _>>()
         .unwrap();
 
-    (directions, Map::new(&paths[..]))
+    (directions, Map::new(&paths[..]).unwrap())
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -81,7 +79,7 @@ impl FromStr for Path {
             .next()
             .ok_or(Error::msg("no_directions"))?
             .trim()
-            .replace(&['(', ')', ','], "");
+            .replace(['(', ')', ','], "");
         let mut to = to.split(char::is_whitespace);
 
         Ok(Path {
@@ -92,54 +90,86 @@ impl FromStr for Path {
     }
 }
 
+/// Nodes are interned; `nodes[id]` holds the (left, right) successors of node `id`
 #[derive(Debug)]
 struct Map {
-    map: HashMap<String, (String, String)>,
+    labels: Interner,
+    nodes: Vec<(u32, u32)>,
 }
 
 impl Map {
-    fn new(paths: &[Path]) -> Map {
-        Map {
-            map: paths
-                .iter()
-                .map(|path| (path.from.clone(), (path.left.clone(), path.right.clone())))
-                .collect(),
+    /// Fails if a node is referenced but never defined
+    fn new(paths: &[Path]) -> Result<Map, Error> {
+        let mut labels = Interner::new();
+        let edges: Vec<(u32, u32, u32)> = paths
+            .iter()
+            .map(|path| {
+                (
+                    labels.intern(&path.from),
+                    labels.intern(&path.left),
+                    labels.intern(&path.right),
+                )
+            })
+            .collect();
+        let mut nodes: Vec<Option<(u32, u32)>> = vec![None; labels.len()];
+        for (from, left, right) in edges {
+            nodes[from as usize] = Some((left, right));
+        }
+        let nodes = nodes
+            .into_iter()
+            .zip(labels.iter())
+            .map(|(node, (_, label))| {
+                node.ok_or_else(|| {
+                    Error::msg(format!("node {label} is referenced but never defined"))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Map { labels, nodes })
+    }
+
+    fn next(&self, node: u32, dir: &Direction) -> u32 {
+        let (left, right) = self.nodes[node as usize];
+        match dir {
+            Direction::Left => left,
+            Direction::Right => right,
         }
     }
 
+    /// Ids of every node whose label ends with `suffix`
+    fn ending_with(&self, suffix: &str) -> Vec<u32> {
+        self.labels
+            .iter()
+            .filter(|(_, label)| label.ends_with(suffix))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
     fn path_length(&self, directions: &Directions) -> usize {
-        let start: String = String::from("AAA");
-        let end: String = String::from("ZZZ");
+        let start = self.labels.get("AAA").expect("start node");
+        let end = self.labels.get("ZZZ").expect("end node");
         let mut cur = start;
         let mut steps = 0;
         for dir in directions.directions.iter().cycle() {
             if cur == end {
                 break;
             };
-            cur = match dir {
-                Direction::Left => self.map.get(&cur).expect("dead path").0.clone(),
-                Direction::Right => self.map.get(&cur).expect("dead path").1.clone(),
-            };
+            cur = self.next(cur, dir);
             steps += 1;
         }
         steps
     }
 
     fn multi_path_length(&self, directions: &Directions) -> usize {
-        let start: Vec<String> = self
-            .map
-            .keys()
-            .filter(|&k| k.ends_with("A"))
-            .cloned()
-            .collect();
-        let mut cur = start.clone();
-        let mut steps = Vec::with_capacity(cur.len());
-        steps.extend(repeat(0).take(cur.len()));
-        let mut step = 0;
-        for dir in directions.directions.iter().cycle() {
+        let mut is_end = vec![false; self.labels.len()];
+        self.ending_with("Z")
+            .into_iter()
+            .for_each(|id| is_end[id as usize] = true);
+        let mut cur = self.ending_with("A");
+        let mut steps = vec![0; cur.len()];
+        for (step, dir) in directions.directions.iter().cycle().enumerate() {
             cur.iter()
                 .enumerate()
-                .filter(|(_, k)| k.ends_with("Z"))
+                .filter(|(_, k)| is_end[**k as usize])
                 .for_each(|(i, _)| {
                     steps[i] = step;
                 });
@@ -147,15 +177,7 @@ impl Map {
             if steps.iter().all(|i| *i > 0) {
                 break;
             }
-            match dir {
-                Direction::Left => cur.iter_mut().for_each(|k| {
-                    *k = self.map.get(k).expect("dead path").0.clone();
-                }),
-                Direction::Right => cur.iter_mut().for_each(|k| {
-                    *k = self.map.get(k).expect("dead path").1.clone();
-                }),
-            };
-            step += 1;
+            cur.iter_mut().for_each(|k| *k = self.next(*k, dir));
         }
         steps.iter().fold(1, |acc, cur| acc.lcm(cur))
     }
@@ -212,7 +234,39 @@ mod test {
             .collect::<Result<Vec<Path>, _>>()
             .unwrap();
 
-        let map = Map::new(&paths[..]);
+        let map = Map::new(&paths[..]).unwrap();
         assert_eq!(6, map.path_length(&directions));
     }
+
+    #[test]
+    fn test_multi_path_example() {
+        let paths = [
+            "11A = (11B, XXX)",
+            "11B = (XXX, 11Z)",
+            "11Z = (11B, XXX)",
+            "22A = (22B, XXX)",
+            "22B = (22C, 22C)",
+            "22C = (22Z, 22Z)",
+            "22Z = (22B, 22B)",
+            "XXX = (XXX, XXX)",
+        ]
+        .into_iter()
+        .map(str::parse::<Path>)
+        .collect::<Result<Vec<Path>, _>>()
+        .unwrap();
+
+        let map = Map::new(&paths[..]).unwrap();
+        let directions = "LR".parse::<Directions>().unwrap();
+        assert_eq!(6, map.multi_path_length(&directions));
+    }
+
+    #[test]
+    fn test_undefined_node() {
+        let paths: Vec<Path> = ["AAA = (BBB, AAA)", "BBB = (AAA, CCC)"]
+            .iter()
+            .map(|l| l.parse().unwrap())
+            .collect();
+        let err = Map::new(&paths).unwrap_err();
+        assert_eq!("node CCC is referenced but never defined", err.to_string());
+    }
 }
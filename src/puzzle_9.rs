@@ -1,42 +1,45 @@
-use crate::util::load_lines;
+use crate::util::parse::{parse_all, signed_list};
+use crate::util::{checked, load_lines};
 
-pub fn puzzle_9_1() -> i32 {
-    load_lines("9/input.txt")
-        .map(|l| parse_line(&l.expect("readline")))
-        .map(|n| prediction(&n[..]))
-        .sum::<i32>()
+pub fn puzzle_9_1() -> i64 {
+    checked::sum(
+        9,
+        load_lines("9/input.txt")
+            .map(|l| parse_line(&l.expect("readline")))
+            .map(|n| prediction(&n[..])),
+    )
 }
 
-pub fn puzzle_9_2() -> i32 {
-    load_lines("9/input.txt")
-        .map(|l| parse_line(&l.expect("readline")))
-        .map(|n| prediction_reversed(&n[..]))
-        .sum::<i32>()
+pub fn puzzle_9_2() -> i64 {
+    checked::sum(
+        9,
+        load_lines("9/input.txt")
+            .map(|l| parse_line(&l.expect("readline")))
+            .map(|n| prediction_reversed(&n[..])),
+    )
 }
 
-fn parse_line(line: &str) -> Vec<i32> {
-    line.split(char::is_whitespace)
-        .map(|s| str::parse::<i32>(s).expect("valid numbers"))
-        .collect()
+fn parse_line(line: &str) -> Vec<i64> {
+    parse_all(line, signed_list).unwrap_or_else(|e| panic!("invalid history:\n{e}"))
 }
 
-fn prediction(data: &[i32]) -> i32 {
-    let mut data: Vec<i32> = data.iter().cloned().collect();
+fn prediction(data: &[i64]) -> i64 {
+    let mut data: Vec<i64> = data.to_vec();
     let mut end: usize = data.len() - 1;
 
     loop {
         if data[..end].iter().all(|v| *v == 0) {
-            return data.iter().sum();
+            return checked::sum(9, data);
         }
         for i in 0..end {
-            data[i] = data[i + 1] - data[i];
+            data[i] = checked::sub(9, data[i + 1], data[i]);
         }
         end -= 1;
     }
 }
 
-fn prediction_reversed(data: &[i32]) -> i32 {
-    let mut data: Vec<i32> = data.iter().cloned().collect();
+fn prediction_reversed(data: &[i64]) -> i64 {
+    let mut data: Vec<i64> = data.to_vec();
     data.reverse();
     prediction(&data[..])
 }
@@ -50,9 +53,9 @@ mod test {
         assert_eq!(0, prediction(&[0, 0, 0]));
     }
 
-    const EXAMPLE_1: &[i32] = &[0, 3, 6, 9, 12, 15];
-    const EXAMPLE_2: &[i32] = &[1, 3, 6, 10, 15, 21];
-    const EXAMPLE_3: &[i32] = &[10, 13, 16, 21, 30, 45];
+    const EXAMPLE_1: &[i64] = &[0, 3, 6, 9, 12, 15];
+    const EXAMPLE_2: &[i64] = &[1, 3, 6, 10, 15, 21];
+    const EXAMPLE_3: &[i64] = &[10, 13, 16, 21, 30, 45];
 
     #[test]
     fn test_examples() {
@@ -5,6 +5,31 @@ use std::io::BufRead;
 use std::ops::{Index, IndexMut};
 use std::path::{Path, PathBuf};
 
+pub mod aho_corasick;
+mod bitgrid;
+pub mod checked;
+mod codec;
+mod counter;
+pub mod geom;
+mod interner;
+pub mod interval;
+pub mod log;
+pub mod memo;
+pub mod parse;
+pub mod polygon;
+mod region;
+pub mod render;
+mod sparse;
+mod view;
+
+pub use bitgrid::BitGrid;
+pub use codec::{CellCodec, CharCodec, GridText, TableCodec};
+pub use counter::Counter;
+pub use interner::Interner;
+pub use region::{Components, Connectivity, Region};
+pub use sparse::{SparseGrid, TiledGrid};
+pub use view::{Column, SubGrid};
+
 const BASE_PATH: &str = env!("CARGO_MANIFEST_DIR");
 const DATA_DIR: &str = "src/data/";
 
@@ -58,6 +83,27 @@ pub struct Grid<T> {
 }
 
 impl<T> Grid<T> {
+    /// Get a cell, or `None` if the position lies outside the grid
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        if pos.0 < self.shape.0 && pos.1 < self.shape.1 {
+            Some(&self[pos.0][pos.1])
+        } else {
+            None
+        }
+    }
+
+    /// Orthogonal neighbours of a position that lie inside the grid (N, E, S, W)
+    pub fn neighbours(&self, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        let (r, c) = (pos.0 as isize, pos.1 as isize);
+        [(r - 1, c), (r, c + 1), (r + 1, c), (r, c - 1)]
+            .into_iter()
+            .filter(|(r, c)| {
+                *r >= 0 && *c >= 0 && (*r as usize) < self.shape.0 && (*c as usize) < self.shape.1
+            })
+            .map(|(r, c)| (r as usize, c as usize))
+            .collect()
+    }
+
     pub fn inspect(&self, f: impl Fn(&T) -> String) -> String {
         let mut s = String::new();
         for row in 0..self.shape.0 {
@@ -88,14 +134,15 @@ impl<T> Grid<T>
 where
     T: From<char>,
 {
+    /// Rectangular line-separated data; a single line is a one-row grid
     pub fn from_table_data(s: &str) -> Self {
-        let cols = s.find('\n').expect("reactangular line-separated data");
+        let cols = s.find('\n').unwrap_or(s.len());
         let data: Vec<T> = s
             .chars()
             .filter(|c| !c.is_whitespace())
             .map(|c| c.into())
             .collect();
-        let rows = data.len() / cols;
+        let rows = data.len().checked_div(cols).unwrap_or(0);
         Grid {
             data,
             shape: (rows, cols),
@@ -106,6 +153,13 @@ impl<T> Grid<T>
 where
     T: Clone,
 {
+    pub fn new(shape: (usize, usize), value: T) -> Self {
+        Grid {
+            data: vec![value; shape.0 * shape.1],
+            shape,
+        }
+    }
+
     pub fn transpose(&mut self) {
         let mut transposed = vec![];
         self.shape = (self.shape.1, self.shape.0);
@@ -0,0 +1,218 @@
+//! Aho–Corasick multi-pattern matching: finds every (overlapping) occurrence of
+//! a fixed set of patterns in a single pass over the text.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Range;
+
+#[derive(Debug, Default, Clone)]
+struct Node {
+    next: BTreeMap<char, usize>,
+    fail: usize,
+    /// Patterns ending here, including those reached through fail links
+    outputs: Vec<usize>,
+}
+
+/// An occurrence of a pattern; `span` holds byte offsets into the text
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Match<'a, V> {
+    pub span: Range<usize>,
+    pub pattern: &'a str,
+    pub value: &'a V,
+}
+
+/// A set of patterns, each tagged with a value
+#[derive(Debug, Clone)]
+pub struct AhoCorasick<V> {
+    nodes: Vec<Node>,
+    /// Pattern, its length in folded chars and its value
+    patterns: Vec<(String, usize, V)>,
+    ignore_case: bool,
+}
+
+const ROOT: usize = 0;
+
+impl<V> AhoCorasick<V> {
+    pub fn new<S: AsRef<str>>(patterns: impl IntoIterator<Item = (S, V)>) -> Self {
+        Self::build(patterns, false)
+    }
+
+    /// Patterns match regardless of letter case
+    pub fn new_ignore_case<S: AsRef<str>>(patterns: impl IntoIterator<Item = (S, V)>) -> Self {
+        Self::build(patterns, true)
+    }
+
+    fn build<S: AsRef<str>>(patterns: impl IntoIterator<Item = (S, V)>, ignore_case: bool) -> Self {
+        let mut ac = AhoCorasick {
+            nodes: vec![Node::default()],
+            patterns: vec![],
+            ignore_case,
+        };
+        for (pattern, value) in patterns {
+            ac.insert(pattern.as_ref(), value);
+        }
+        ac.link();
+        ac
+    }
+
+    // Lowercased with `ignore_case`; a char may fold into several
+    fn fold(&self, c: char) -> impl Iterator<Item = char> {
+        let ignore_case = self.ignore_case;
+        c.to_lowercase()
+            .filter(move |_| ignore_case)
+            .chain(Some(c).filter(move |_| !ignore_case))
+    }
+
+    fn insert(&mut self, pattern: &str, value: V) {
+        let mut node = ROOT;
+        let folded: Vec<char> = pattern.chars().flat_map(|c| self.fold(c)).collect();
+        for &c in folded.iter() {
+            node = match self.nodes[node].next.get(&c) {
+                Some(&next) => next,
+                None => {
+                    self.nodes.push(Node::default());
+                    let next = self.nodes.len() - 1;
+                    self.nodes[node].next.insert(c, next);
+                    next
+                }
+            };
+        }
+        self.nodes[node].outputs.push(self.patterns.len());
+        self.patterns
+            .push((pattern.to_string(), folded.len(), value));
+    }
+
+    // Breadth first, so every fail target is complete before it is used
+    fn link(&mut self) {
+        let mut queue: VecDeque<usize> = self.nodes[ROOT].next.values().copied().collect();
+        while let Some(node) = queue.pop_front() {
+            let edges: Vec<(char, usize)> = self.nodes[node]
+                .next
+                .iter()
+                .map(|(c, n)| (*c, *n))
+                .collect();
+            for (c, child) in edges {
+                let mut fail = self.nodes[node].fail;
+                let target = loop {
+                    if let Some(&next) = self.nodes[fail].next.get(&c) {
+                        break next;
+                    }
+                    if fail == ROOT {
+                        break ROOT;
+                    }
+                    fail = self.nodes[fail].fail;
+                };
+                self.nodes[child].fail = target;
+                let inherited = self.nodes[target].outputs.clone();
+                self.nodes[child].outputs.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+    }
+
+    fn step(&self, mut node: usize, c: char) -> usize {
+        loop {
+            if let Some(&next) = self.nodes[node].next.get(&c) {
+                return next;
+            }
+            if node == ROOT {
+                return ROOT;
+            }
+            node = self.nodes[node].fail;
+        }
+    }
+
+    /// Every occurrence of every pattern, ordered by end offset.
+    /// Occurrences ending at the same offset are ordered longest first.
+    pub fn find_all<'a>(&'a self, text: &str) -> Vec<Match<'a, V>> {
+        let mut matches = vec![];
+        let mut node = ROOT;
+        // Byte offset of the text char each folded char came from
+        let mut starts = vec![];
+        for (offset, c) in text.char_indices() {
+            let end = offset + c.len_utf8();
+            for folded in self.fold(c) {
+                starts.push(offset);
+                node = self.step(node, folded);
+                let mut found: Vec<Match<V>> = self.nodes[node]
+                    .outputs
+                    .iter()
+                    .map(|&p| {
+                        let (pattern, len, value) = &self.patterns[p];
+                        Match {
+                            span: starts[starts.len() - len]..end,
+                            pattern: pattern.as_str(),
+                            value,
+                        }
+                    })
+                    .collect();
+                found.sort_by_key(|m| m.span.start);
+                matches.extend(found);
+            }
+        }
+        matches
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn spans<'a, V>(matches: &[Match<'a, V>]) -> Vec<(&'a str, Range<usize>)> {
+        matches
+            .iter()
+            .map(|m| (m.pattern, m.span.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let ac = AhoCorasick::new([("he", 1), ("she", 2), ("his", 3), ("hers", 4)]);
+        let matches = ac.find_all("ushers");
+        assert_eq!(
+            vec![("she", 1..4), ("he", 2..4), ("hers", 2..6)],
+            spans(&matches)
+        );
+        assert_eq!(&2, matches[0].value);
+    }
+
+    #[test]
+    fn test_digit_words() {
+        let ac = AhoCorasick::new([("one", 1), ("eight", 8), ("two", 2), ("3", 3)]);
+        let matches = ac.find_all("eightwone3");
+        assert_eq!(
+            vec![("eight", 0..5), ("two", 4..7), ("one", 6..9), ("3", 9..10)],
+            spans(&matches)
+        );
+        assert!(ac.find_all("").is_empty());
+        assert!(ac.find_all("xyz").is_empty());
+    }
+
+    #[test]
+    fn test_nested_and_repeated() {
+        let ac = AhoCorasick::new([("a", 'a'), ("aa", 'b'), ("aaa", 'c')]);
+        assert_eq!(6, ac.find_all("aaa").len());
+        assert_eq!(3, ac.len());
+
+        let ac = AhoCorasick::new([("über", ())]);
+        assert_eq!(vec![("über", 1..6)], spans(&ac.find_all("xüber")));
+    }
+
+    #[test]
+    fn test_ignore_case() {
+        let ac = AhoCorasick::new_ignore_case([("Fünf", 5), ("ZWEI", 2)]);
+        assert_eq!(
+            vec![("ZWEI", 0..4), ("Fünf", 4..9)],
+            spans(&ac.find_all("zweiFÜNF"))
+        );
+        let ac = AhoCorasick::new([("Fünf", 5)]);
+        assert!(ac.find_all("FÜNF").is_empty());
+    }
+}
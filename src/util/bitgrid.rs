@@ -0,0 +1,294 @@
+use super::geom::Direction;
+use super::Grid;
+use std::fmt::Display;
+use std::ops::{BitAndAssign, BitOrAssign, BitXorAssign};
+
+const WORD: usize = u64::BITS as usize;
+
+/// A boolean grid packing one cell per bit.
+/// Each row is a run of `u64` words; column `c` lives in bit `c % 64` of word `c / 64`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BitGrid {
+    words: Vec<u64>,
+    words_per_row: usize,
+    pub shape: (usize, usize),
+}
+
+impl BitGrid {
+    pub fn new(shape: (usize, usize)) -> Self {
+        let words_per_row = shape.1.div_ceil(WORD);
+        BitGrid {
+            words: vec![0; shape.0 * words_per_row],
+            words_per_row,
+            shape,
+        }
+    }
+
+    /// Set every cell of `grid` for which `predicate` holds
+    pub fn from_grid<T>(grid: &Grid<T>, predicate: impl Fn(&T) -> bool) -> Self {
+        let mut bits = BitGrid::new(grid.shape);
+        for row in 0..grid.shape.0 {
+            for col in 0..grid.shape.1 {
+                if predicate(&grid[row][col]) {
+                    bits.set(row, col, true);
+                }
+            }
+        }
+        bits
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        let (word, bit) = self.locate(row, col);
+        self.words[word] >> bit & 1 == 1
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        let (word, bit) = self.locate(row, col);
+        if value {
+            self.words[word] |= 1 << bit;
+        } else {
+            self.words[word] &= !(1 << bit);
+        }
+    }
+
+    pub fn toggle(&mut self, row: usize, col: usize) {
+        let (word, bit) = self.locate(row, col);
+        self.words[word] ^= 1 << bit;
+    }
+
+    /// The packed words of a single row
+    pub fn row_words(&self, row: usize) -> &[u64] {
+        &self.words[row * self.words_per_row..(row + 1) * self.words_per_row]
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn row_count_ones(&self, row: usize) -> usize {
+        self.row_words(row)
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// Flip every cell
+    pub fn invert(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = !*w);
+        self.mask_padding();
+    }
+
+    /// Move every cell one step towards `dir`; cells pushed over the edge are lost
+    pub fn shifted(&self, dir: Direction) -> BitGrid {
+        // No words to move, and `chunks` cannot take a size of 0
+        if self.words_per_row == 0 {
+            return self.clone();
+        }
+        let mut out = BitGrid::new(self.shape);
+        let n = self.words_per_row;
+        match dir {
+            Direction::North => out.words[..self.words.len().saturating_sub(n)]
+                .copy_from_slice(&self.words[n.min(self.words.len())..]),
+            Direction::South => out.words[n.min(self.words.len())..]
+                .copy_from_slice(&self.words[..self.words.len().saturating_sub(n)]),
+            Direction::East => {
+                for (src, dst) in self.words.chunks(n).zip(out.words.chunks_mut(n)) {
+                    let mut carry = 0;
+                    for (s, d) in src.iter().zip(dst.iter_mut()) {
+                        *d = s << 1 | carry;
+                        carry = s >> (WORD - 1);
+                    }
+                }
+                out.mask_padding();
+            }
+            Direction::West => {
+                for (src, dst) in self.words.chunks(n).zip(out.words.chunks_mut(n)) {
+                    let mut carry = 0;
+                    for (s, d) in src.iter().zip(dst.iter_mut()).rev() {
+                        *d = s >> 1 | carry;
+                        carry = s << (WORD - 1);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Every cell set here or orthogonally adjacent to one
+    pub fn dilated(&self) -> BitGrid {
+        let mut out = self.clone();
+        for dir in Direction::ALL {
+            out |= &self.shifted(dir);
+        }
+        out
+    }
+
+    /// All passable cells reachable from `seed`, grown one word-parallel step at a time
+    pub fn flood(seed: &BitGrid, passable: &BitGrid) -> BitGrid {
+        let mut filled = seed.clone();
+        filled &= passable;
+        loop {
+            let mut next = filled.dilated();
+            next &= passable;
+            if next == filled {
+                return filled;
+            }
+            filled = next;
+        }
+    }
+
+    fn locate(&self, row: usize, col: usize) -> (usize, usize) {
+        assert!(
+            row < self.shape.0 && col < self.shape.1,
+            "({row}, {col}) outside bit grid {:?}",
+            self.shape
+        );
+        (row * self.words_per_row + col / WORD, col % WORD)
+    }
+
+    // Keep bits past the last column cleared so counts and comparisons stay exact
+    fn mask_padding(&mut self) {
+        let used = self.shape.1 % WORD;
+        if used == 0 {
+            return;
+        }
+        let mask = (1u64 << used) - 1;
+        for row in self.words.chunks_mut(self.words_per_row) {
+            if let Some(last) = row.last_mut() {
+                *last &= mask;
+            }
+        }
+    }
+
+    fn zip_words(&mut self, other: &BitGrid, op: impl Fn(&mut u64, u64)) {
+        assert_eq!(self.shape, other.shape, "bit grids differ in shape");
+        self.words
+            .iter_mut()
+            .zip(other.words.iter())
+            .for_each(|(a, b)| op(a, *b));
+    }
+}
+
+impl BitAndAssign<&BitGrid> for BitGrid {
+    fn bitand_assign(&mut self, rhs: &BitGrid) {
+        self.zip_words(rhs, |a, b| *a &= b);
+    }
+}
+
+impl BitOrAssign<&BitGrid> for BitGrid {
+    fn bitor_assign(&mut self, rhs: &BitGrid) {
+        self.zip_words(rhs, |a, b| *a |= b);
+    }
+}
+
+impl BitXorAssign<&BitGrid> for BitGrid {
+    fn bitxor_assign(&mut self, rhs: &BitGrid) {
+        self.zip_words(rhs, |a, b| *a ^= b);
+    }
+}
+
+impl Display for BitGrid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..self.shape.0 {
+            for col in 0..self.shape.1 {
+                f.write_str(if self.get(row, col) { "#" } else { "." })?;
+            }
+            f.write_str("\n")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_get() {
+        let mut bits = BitGrid::new((3, 130));
+        bits.set(1, 0, true);
+        bits.set(1, 64, true);
+        bits.set(2, 129, true);
+        bits.toggle(0, 63);
+        assert!(bits.get(1, 64));
+        assert!(!bits.get(1, 63));
+        assert!(bits.get(0, 63));
+        assert_eq!(4, bits.count_ones());
+        assert_eq!(2, bits.row_count_ones(1));
+        assert_eq!([1, 1, 0], bits.row_words(1));
+
+        bits.set(1, 64, false);
+        assert_eq!(3, bits.count_ones());
+    }
+
+    #[test]
+    fn test_shift_across_words() {
+        let mut bits = BitGrid::new((2, 70));
+        bits.set(0, 63, true);
+        bits.set(0, 69, true);
+
+        let east = bits.shifted(Direction::East);
+        assert!(east.get(0, 64));
+        assert_eq!(1, east.count_ones()); // Column 69 falls off the edge
+
+        let west = bits.shifted(Direction::West);
+        assert!(west.get(0, 62));
+        assert!(west.get(0, 68));
+
+        let south = bits.shifted(Direction::South);
+        assert!(south.get(1, 63) && south.get(1, 69));
+        assert!(south.shifted(Direction::South).is_empty());
+        assert_eq!(bits, south.shifted(Direction::North));
+    }
+
+    #[test]
+    fn test_shift_zero_width() {
+        let grid = BitGrid::new((3, 0));
+        for dir in Direction::ALL {
+            assert_eq!(grid, grid.shifted(dir));
+        }
+        assert_eq!(grid, BitGrid::flood(&grid, &grid));
+    }
+
+    #[test]
+    fn test_logic_ops() {
+        let grid = Grid::<char>::from_table_data("#.#\n.##");
+        let a = BitGrid::from_grid(&grid, |c| *c == '#');
+        let mut b = a.clone();
+        b.invert();
+        assert_eq!(2, b.count_ones());
+
+        let mut union = a.clone();
+        union |= &b;
+        assert_eq!(6, union.count_ones());
+
+        let mut inter = a.clone();
+        inter &= &b;
+        assert!(inter.is_empty());
+
+        let mut diff = union;
+        diff ^= &a;
+        assert_eq!(b, diff);
+        assert_eq!(".#.\n#..\n", b.to_string());
+    }
+
+    #[test]
+    fn test_flood() {
+        let grid = Grid::<char>::from_table_data("..#..\n..#..\n.###.\n.....");
+        let passable = BitGrid::from_grid(&grid, |c| *c == '.');
+        let mut seed = BitGrid::new(grid.shape);
+        seed.set(0, 0, true);
+        let filled = BitGrid::flood(&seed, &passable);
+        assert_eq!(passable, filled);
+
+        let mut walled = passable.clone();
+        walled.set(3, 2, false);
+        let filled = BitGrid::flood(&seed, &walled);
+        assert_eq!(7, filled.count_ones());
+        assert!(!filled.get(0, 4));
+    }
+}
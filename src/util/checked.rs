@@ -0,0 +1,102 @@
+//! Answer arithmetic that can be switched to overflow checking.
+//!
+//! With the `checked` cargo feature every operation is checked, and an overflow
+//! panics with the day and the operation that overflowed. Without it they are
+//! the plain operators, which only panic in debug builds.
+
+use num::{CheckedAdd, CheckedMul, CheckedSub};
+use std::fmt::Display;
+
+#[cold]
+#[track_caller]
+fn overflow(day: u32, op: &str, a: impl Display, b: impl Display) -> ! {
+    panic!("day {day}: overflow in {a} {op} {b}")
+}
+
+#[track_caller]
+pub fn add<T>(day: u32, a: T, b: T) -> T
+where
+    T: CheckedAdd + Display,
+{
+    if cfg!(feature = "checked") {
+        a.checked_add(&b)
+            .unwrap_or_else(|| overflow(day, "+", a, b))
+    } else {
+        a + b
+    }
+}
+
+#[track_caller]
+pub fn sub<T>(day: u32, a: T, b: T) -> T
+where
+    T: CheckedSub + Display,
+{
+    if cfg!(feature = "checked") {
+        a.checked_sub(&b)
+            .unwrap_or_else(|| overflow(day, "-", a, b))
+    } else {
+        a - b
+    }
+}
+
+#[track_caller]
+pub fn mul<T>(day: u32, a: T, b: T) -> T
+where
+    T: CheckedMul + Display,
+{
+    if cfg!(feature = "checked") {
+        a.checked_mul(&b)
+            .unwrap_or_else(|| overflow(day, "*", a, b))
+    } else {
+        a * b
+    }
+}
+
+/// Sum of all items, starting from zero
+#[track_caller]
+pub fn sum<T>(day: u32, items: impl IntoIterator<Item = T>) -> T
+where
+    T: CheckedAdd + Display + num::Zero,
+{
+    items
+        .into_iter()
+        .fold(T::zero(), |total, item| add(day, total, item))
+}
+
+/// Convert between integer types, reporting values that do not fit
+#[track_caller]
+pub fn convert<T, U>(day: u32, value: T) -> U
+where
+    T: Copy + Display + TryInto<U>,
+{
+    value
+        .try_into()
+        .unwrap_or_else(|_| panic!("day {day}: {value} does not fit into the answer type"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_in_range() {
+        assert_eq!(7u8, add(1, 3, 4));
+        assert_eq!(-1i32, sub(1, 3, 4));
+        assert_eq!(12u64, mul(1, 3, 4));
+        assert_eq!(10i64, sum(1, [1, 2, 3, 4]));
+        assert_eq!(300u64, convert(1, 300i128));
+    }
+
+    #[test]
+    #[cfg(feature = "checked")]
+    #[should_panic(expected = "day 4: overflow in 200 + 100")]
+    fn test_add_overflow() {
+        add(4, 200u8, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "day 18: -1 does not fit")]
+    fn test_convert_out_of_range() {
+        let _: u64 = convert(18, -1i128);
+    }
+}
@@ -0,0 +1,204 @@
+use super::Grid;
+use anyhow::{Error, Result};
+use std::path::Path;
+
+/// Converts between grid cells and the characters of a puzzle's text format
+pub trait CellCodec<T> {
+    fn decode(&self, c: char) -> Option<T>;
+    fn encode(&self, cell: &T) -> char;
+}
+
+/// Cells are the characters themselves
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CharCodec;
+
+impl CellCodec<char> for CharCodec {
+    fn decode(&self, c: char) -> Option<char> {
+        Some(c)
+    }
+
+    fn encode(&self, cell: &char) -> char {
+        *cell
+    }
+}
+
+/// A fixed lookup table of (character, cell) pairs
+#[derive(Debug, Clone)]
+pub struct TableCodec<T> {
+    table: Vec<(char, T)>,
+}
+
+impl<T> TableCodec<T> {
+    pub fn new(table: Vec<(char, T)>) -> Self {
+        TableCodec { table }
+    }
+}
+
+impl<T> CellCodec<T> for TableCodec<T>
+where
+    T: PartialEq + Clone,
+{
+    fn decode(&self, c: char) -> Option<T> {
+        self.table
+            .iter()
+            .find(|(ch, _)| *ch == c)
+            .map(|(_, cell)| cell.clone())
+    }
+
+    fn encode(&self, cell: &T) -> char {
+        self.table
+            .iter()
+            .find(|(_, t)| t == cell)
+            .map(|(ch, _)| *ch)
+            .expect("every cell value has a character")
+    }
+}
+
+impl<T> Grid<T> {
+    /// Parse line-separated rows of equal length, one character per cell.
+    /// Errors give 1-based rows and columns.
+    pub fn parse_with(s: &str, codec: &impl CellCodec<T>) -> Result<Self> {
+        let mut data = vec![];
+        let mut cols = None;
+        let mut rows = 0;
+        for (i, line) in s.lines().map(|l| l.trim_end_matches('\r')).enumerate() {
+            let row = i + 1;
+            let len = line.chars().count();
+            if *cols.get_or_insert(len) != len {
+                return Err(Error::msg(format!(
+                    "row {row} has {len} cells, expected {}",
+                    cols.unwrap_or(0)
+                )));
+            }
+            for (j, c) in line.chars().enumerate() {
+                let cell = codec.decode(c).ok_or_else(|| {
+                    Error::msg(format!("invalid cell {c:?} at row {row}, column {}", j + 1))
+                })?;
+                data.push(cell);
+            }
+            rows += 1;
+        }
+        Ok(Grid {
+            data,
+            shape: (rows, cols.unwrap_or(0)),
+        })
+    }
+
+    /// Render in the puzzle's text format, rows separated by newlines
+    pub fn to_text_with(&self, codec: &impl CellCodec<T>) -> String {
+        self.join_rows(codec, "\n")
+    }
+
+    fn join_rows(&self, codec: &impl CellCodec<T>, separator: &str) -> String {
+        let rows: Vec<String> = (0..self.shape.0)
+            .map(|row| self[row].iter().map(|cell| codec.encode(cell)).collect())
+            .collect();
+        rows.join(separator)
+    }
+}
+
+/// A grid together with its line endings, so it can be written back byte for byte
+/// as long as every line ends the same way
+#[derive(Debug, PartialEq, Eq)]
+pub struct GridText<T> {
+    pub grid: Grid<T>,
+    /// Lines end in `\r\n` rather than `\n`, going by the first one
+    pub crlf: bool,
+    pub trailing_newline: bool,
+}
+
+impl<T> GridText<T> {
+    pub fn parse_with(s: &str, codec: &impl CellCodec<T>) -> Result<Self> {
+        Ok(GridText {
+            grid: Grid::parse_with(s, codec)?,
+            crlf: s.find('\n').is_some_and(|i| s[..i].ends_with('\r')),
+            trailing_newline: s.ends_with('\n'),
+        })
+    }
+
+    pub fn to_text_with(&self, codec: &impl CellCodec<T>) -> String {
+        let newline = if self.crlf { "\r\n" } else { "\n" };
+        let mut s = self.grid.join_rows(codec, newline);
+        if self.trailing_newline {
+            s.push_str(newline);
+        }
+        s
+    }
+
+    pub fn read_with(path: impl AsRef<Path>, codec: &impl CellCodec<T>) -> Result<Self> {
+        Self::parse_with(&std::fs::read_to_string(path)?, codec)
+    }
+
+    pub fn write_with(&self, path: impl AsRef<Path>, codec: &impl CellCodec<T>) -> Result<()> {
+        std::fs::write(path, self.to_text_with(codec))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    enum Rock {
+        Round,
+        Cube,
+        Empty,
+    }
+
+    fn rocks() -> TableCodec<Rock> {
+        TableCodec::new(vec![
+            ('O', Rock::Round),
+            ('#', Rock::Cube),
+            ('.', Rock::Empty),
+        ])
+    }
+
+    const PLATFORM: &str = "O....#\n.#..O.\n..O#..\n";
+
+    #[test]
+    fn test_round_trip() {
+        let grid = Grid::parse_with(PLATFORM, &rocks()).unwrap();
+        assert_eq!((3, 6), grid.shape);
+        assert_eq!(Rock::Round, grid[0][0]);
+        assert_eq!(Rock::Cube, grid[2][3]);
+        assert_eq!(PLATFORM.trim_end(), grid.to_text_with(&rocks()));
+
+        let chars = GridText::parse_with(PLATFORM, &CharCodec).unwrap();
+        assert!(chars.trailing_newline);
+        assert_eq!(PLATFORM, chars.to_text_with(&CharCodec));
+
+        let unterminated = PLATFORM.trim_end();
+        let text = GridText::parse_with(unterminated, &rocks()).unwrap();
+        assert!(!text.trailing_newline);
+        assert_eq!(unterminated, text.to_text_with(&rocks()));
+
+        let windows = PLATFORM.replace('\n', "\r\n");
+        let text = GridText::parse_with(&windows, &rocks()).unwrap();
+        assert!(text.crlf && text.trailing_newline);
+        assert_eq!(chars.grid.shape, text.grid.shape);
+        assert_eq!(windows, text.to_text_with(&rocks()));
+        let unterminated = windows.trim_end();
+        let text = GridText::parse_with(unterminated, &rocks()).unwrap();
+        assert_eq!(unterminated, text.to_text_with(&rocks()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Grid::parse_with("O.\n.x\n", &rocks()).unwrap_err();
+        assert_eq!("invalid cell 'x' at row 2, column 2", err.to_string());
+
+        let err = Grid::parse_with("O.\n...\n", &rocks()).unwrap_err();
+        assert_eq!("row 2 has 3 cells, expected 2", err.to_string());
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("aoc_codec_{}.txt", std::process::id()));
+        let text = GridText::parse_with(PLATFORM, &rocks()).unwrap();
+        text.write_with(&path, &rocks()).unwrap();
+        assert_eq!(PLATFORM, std::fs::read_to_string(&path).unwrap());
+        assert_eq!(text, GridText::read_with(&path, &rocks()).unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,196 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::ops::{Add, Sub};
+
+/// A multiset counting how often each item occurs.
+/// Items with a zero count are never stored.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Counter<T>
+where
+    T: Ord,
+{
+    counts: BTreeMap<T, usize>,
+}
+
+impl<T> Default for Counter<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Counter {
+            counts: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> Counter<T>
+where
+    T: Ord,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, item: T) {
+        self.add_n(item, 1);
+    }
+
+    pub fn add_n(&mut self, item: T, n: usize) {
+        if n > 0 {
+            *self.counts.entry(item).or_insert(0) += n;
+        }
+    }
+
+    /// Take away up to `n` occurrences of `item`
+    pub fn remove_n(&mut self, item: &T, n: usize) {
+        if let Some(count) = self.counts.get_mut(item) {
+            *count = count.saturating_sub(n);
+            if *count == 0 {
+                self.counts.remove(item);
+            }
+        }
+    }
+
+    /// Drop `item` entirely, returning how often it occurred
+    pub fn take(&mut self, item: &T) -> usize {
+        self.counts.remove(item).unwrap_or(0)
+    }
+
+    pub fn get(&self, item: &T) -> usize {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    /// Number of distinct items
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Number of items, counting repeats
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Items with their counts, in item order
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.counts.iter().map(|(item, count)| (item, *count))
+    }
+
+    /// All counts, largest first
+    pub fn counts(&self) -> Vec<usize> {
+        let mut counts: Vec<usize> = self.counts.values().copied().collect();
+        counts.sort_by_key(|count| Reverse(*count));
+        counts
+    }
+
+    /// The `n` most frequent items, largest count first; ties keep item order
+    pub fn most_common(&self, n: usize) -> Vec<(&T, usize)> {
+        let mut items: Vec<(&T, usize)> = self.iter().collect();
+        items.sort_by_key(|(_, count)| Reverse(*count));
+        items.truncate(n);
+        items
+    }
+
+    /// Add every count of `other` to this counter
+    pub fn merge(&mut self, other: Counter<T>) {
+        other
+            .counts
+            .into_iter()
+            .for_each(|(item, n)| self.add_n(item, n));
+    }
+}
+
+impl<T> FromIterator<T> for Counter<T>
+where
+    T: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut counter = Counter::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<T> Extend<T> for Counter<T>
+where
+    T: Ord,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|item| self.add(item));
+    }
+}
+
+impl<T> Add for Counter<T>
+where
+    T: Ord,
+{
+    type Output = Counter<T>;
+
+    fn add(mut self, rhs: Counter<T>) -> Counter<T> {
+        self.merge(rhs);
+        self
+    }
+}
+
+/// Counts that would drop below zero are removed
+impl<T> Sub for Counter<T>
+where
+    T: Ord,
+{
+    type Output = Counter<T>;
+
+    fn sub(mut self, rhs: Counter<T>) -> Counter<T> {
+        rhs.iter().for_each(|(item, n)| self.remove_n(item, n));
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        let counter: Counter<char> = "abracadabra".chars().collect();
+        assert_eq!(5, counter.get(&'a'));
+        assert_eq!(0, counter.get(&'z'));
+        assert_eq!(5, counter.len());
+        assert_eq!(11, counter.total());
+        assert_eq!(vec![5, 2, 2, 1, 1], counter.counts());
+        assert_eq!(
+            vec![(&'a', 5), (&'b', 2), (&'r', 2)],
+            counter.most_common(3)
+        );
+        assert_eq!(5, counter.most_common(10).len());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a: Counter<char> = "aabc".chars().collect();
+        let b: Counter<char> = "abbd".chars().collect();
+        assert_eq!(
+            "aaabbbcd".chars().collect::<Counter<_>>(),
+            a.clone() + b.clone()
+        );
+
+        let diff = a.clone() - b;
+        assert_eq!(vec![(&'a', 1), (&'c', 1)], diff.iter().collect::<Vec<_>>());
+        assert!((a.clone() - a).is_empty());
+    }
+
+    #[test]
+    fn test_take_and_remove() {
+        let mut counter: Counter<u8> = [1, 1, 2, 3, 3, 3].into_iter().collect();
+        assert_eq!(2, counter.take(&1));
+        assert_eq!(0, counter.take(&1));
+        counter.remove_n(&3, 2);
+        assert_eq!(1, counter.get(&3));
+        counter.remove_n(&3, 5);
+        assert_eq!(vec![(&2, 1)], counter.iter().collect::<Vec<_>>());
+        counter.add_n(4, 0);
+        assert_eq!(1, counter.len());
+    }
+}
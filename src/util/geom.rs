@@ -0,0 +1,241 @@
+//! Shared positions and directions for grid puzzles.
+//!
+//! Rows grow downwards: `North` decreases the row, `East` increases the column.
+
+use super::Grid;
+use std::ops::{Add, Index, IndexMut, Mul, Sub};
+
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Pos {
+    pub row: isize,
+    pub col: isize,
+}
+
+impl Pos {
+    pub const fn new(row: isize, col: isize) -> Self {
+        Pos { row, col }
+    }
+
+    /// The neighbouring position one step towards `dir`
+    pub fn step(self, dir: Direction) -> Pos {
+        self + dir.offset()
+    }
+
+    /// The position `dist` steps towards `dir`
+    pub fn offset(self, dir: Direction, dist: isize) -> Pos {
+        self + dir.offset() * dist
+    }
+
+    /// Orthogonal neighbours in `Direction::ALL` order
+    pub fn neighbours(self) -> [Pos; 4] {
+        Direction::ALL.map(|dir| self.step(dir))
+    }
+
+    pub fn manhattan(self, other: Pos) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+
+    pub fn chebyshev(self, other: Pos) -> usize {
+        self.row
+            .abs_diff(other.row)
+            .max(self.col.abs_diff(other.col))
+    }
+
+    /// Convert to unsigned (row, col) if both coordinates are non-negative
+    pub fn to_index(self) -> Option<(usize, usize)> {
+        if self.row < 0 || self.col < 0 {
+            None
+        } else {
+            Some((self.row as usize, self.col as usize))
+        }
+    }
+}
+
+impl From<(usize, usize)> for Pos {
+    fn from(index: (usize, usize)) -> Self {
+        Pos::new(index.0 as isize, index.1 as isize)
+    }
+}
+
+impl Add for Pos {
+    type Output = Pos;
+
+    fn add(self, rhs: Pos) -> Pos {
+        Pos::new(self.row + rhs.row, self.col + rhs.col)
+    }
+}
+
+impl Sub for Pos {
+    type Output = Pos;
+
+    fn sub(self, rhs: Pos) -> Pos {
+        Pos::new(self.row - rhs.row, self.col - rhs.col)
+    }
+}
+
+impl Mul<isize> for Pos {
+    type Output = Pos;
+
+    fn mul(self, rhs: isize) -> Pos {
+        Pos::new(self.row * rhs, self.col * rhs)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+/// A change of heading relative to the current direction
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum Turn {
+    Straight,
+    Right,
+    Back,
+    Left,
+}
+
+impl Direction {
+    /// Clockwise, starting at `North`
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    fn from_quarter_turns(n: usize) -> Direction {
+        Direction::ALL[n % 4]
+    }
+
+    fn quarter_turns(self) -> usize {
+        self as usize
+    }
+
+    /// Unit offset of a single step
+    pub fn offset(self) -> Pos {
+        match self {
+            Direction::North => Pos::new(-1, 0),
+            Direction::East => Pos::new(0, 1),
+            Direction::South => Pos::new(1, 0),
+            Direction::West => Pos::new(0, -1),
+        }
+    }
+
+    pub fn turn(self, turn: Turn) -> Direction {
+        Direction::from_quarter_turns(self.quarter_turns() + turn as usize)
+    }
+
+    pub fn turn_left(self) -> Direction {
+        self.turn(Turn::Left)
+    }
+
+    pub fn turn_right(self) -> Direction {
+        self.turn(Turn::Right)
+    }
+
+    pub fn reverse(self) -> Direction {
+        self.turn(Turn::Back)
+    }
+
+    /// The turn that changes heading from `self` to `other`
+    pub fn turn_to(self, other: Direction) -> Turn {
+        match (other.quarter_turns() + 4 - self.quarter_turns()) % 4 {
+            0 => Turn::Straight,
+            1 => Turn::Right,
+            2 => Turn::Back,
+            _ => Turn::Left,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn contains(&self, pos: Pos) -> bool {
+        matches!(pos.to_index(), Some((r, c)) if r < self.shape.0 && c < self.shape.1)
+    }
+
+    /// Get a cell by signed position, or `None` if it lies outside the grid
+    pub fn at(&self, pos: Pos) -> Option<&T> {
+        if self.contains(pos) {
+            Some(&self[pos])
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &Self::Output {
+        &self[pos.row as usize][pos.col as usize]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut Self::Output {
+        &mut self[pos.row as usize][pos.col as usize]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use Direction::*;
+
+    #[test]
+    fn test_turns() {
+        assert_eq!(West, North.turn_left());
+        assert_eq!(East, North.turn_right());
+        assert_eq!(South, North.reverse());
+        assert_eq!(North, West.turn_right());
+        assert_eq!(West, West.turn(Turn::Straight));
+        for dir in Direction::ALL {
+            assert_eq!(dir, dir.turn_left().turn_right());
+            assert_eq!(dir, dir.reverse().reverse());
+            for other in Direction::ALL {
+                assert_eq!(other, dir.turn(dir.turn_to(other)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_steps() {
+        let origin = Pos::new(0, 0);
+        assert_eq!(Pos::new(-1, 0), origin.step(North));
+        assert_eq!(Pos::new(0, 1), origin.step(East));
+        assert_eq!(Pos::new(5, 0), origin.offset(South, 5));
+        assert_eq!(
+            [
+                Pos::new(1, 2),
+                Pos::new(2, 3),
+                Pos::new(3, 2),
+                Pos::new(2, 1)
+            ],
+            Pos::new(2, 2).neighbours()
+        );
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = Pos::new(-2, 3);
+        let b = Pos::new(4, 1);
+        assert_eq!(8, a.manhattan(b));
+        assert_eq!(6, a.chebyshev(b));
+        assert_eq!(0, a.manhattan(a));
+    }
+
+    #[test]
+    fn test_grid_index() {
+        let mut grid = Grid::<char>::from_table_data("123\n456");
+        assert_eq!('6', grid[Pos::new(1, 2)]);
+        assert_eq!('2', grid[0][1]);
+        assert_eq!(None, grid.at(Pos::new(-1, 0)));
+        assert_eq!(None, grid.at(Pos::new(2, 0)));
+        grid[Pos::new(0, 0)] = 'x';
+        assert_eq!(Some(&'x'), grid.at(Pos::new(0, 0)));
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+/// Maps labels to compact ids, handed out in first-seen order starting at 0
+#[derive(Debug, Default, Clone)]
+pub struct Interner {
+    ids: HashMap<String, u32>,
+    labels: Vec<String>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id of `label`, assigning the next free one if it is new
+    pub fn intern(&mut self, label: &str) -> u32 {
+        if let Some(id) = self.ids.get(label) {
+            return *id;
+        }
+        let id = u32::try_from(self.labels.len()).expect("fewer than 2^32 labels");
+        self.labels.push(label.to_string());
+        self.ids.insert(label.to_string(), id);
+        id
+    }
+
+    /// The id of an already interned label
+    pub fn get(&self, label: &str) -> Option<u32> {
+        self.ids.get(label).copied()
+    }
+
+    /// Panics if `id` was not handed out by this interner
+    pub fn label(&self, id: u32) -> &str {
+        &self.labels[id as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// All (id, label) pairs in id order
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> {
+        (0..).zip(self.labels.iter().map(String::as_str))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let mut labels = Interner::new();
+        assert!(labels.is_empty());
+        assert_eq!(0, labels.intern("AAA"));
+        assert_eq!(1, labels.intern("BBB"));
+        assert_eq!(0, labels.intern("AAA"));
+        assert_eq!(2, labels.len());
+        assert_eq!(Some(1), labels.get("BBB"));
+        assert_eq!(None, labels.get("ZZZ"));
+        assert_eq!("BBB", labels.label(1));
+        assert_eq!(
+            vec![(0, "AAA"), (1, "BBB")],
+            labels.iter().collect::<Vec<_>>()
+        );
+    }
+}
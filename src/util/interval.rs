@@ -0,0 +1,313 @@
+//! Half-open intervals and normalized interval sets for range-splitting puzzles.
+
+use num::Zero;
+use std::ops::Sub;
+
+/// The half-open interval `start..end`; empty whenever `start >= end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T> Interval<T>
+where
+    T: Copy + Ord,
+{
+    pub fn new(start: T, end: T) -> Self {
+        Interval { start, end }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn contains(&self, val: T) -> bool {
+        self.start <= val && val < self.end
+    }
+
+    /// Whether the two intervals share at least one value
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        !self.is_empty() && !other.is_empty() && self.start < other.end && other.start < self.end
+    }
+
+    /// Whether the two intervals overlap or are directly adjacent
+    pub fn touches(&self, other: &Interval<T>) -> bool {
+        !self.is_empty() && !other.is_empty() && self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        let i = Interval::new(self.start.max(other.start), self.end.min(other.end));
+        (!i.is_empty()).then_some(i)
+    }
+
+    /// The parts of `self` not covered by `other`, in ascending order
+    pub fn difference(&self, other: &Interval<T>) -> Vec<Interval<T>> {
+        if !self.overlaps(other) {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        }
+        [
+            Interval::new(self.start, other.start),
+            Interval::new(other.end, self.end),
+        ]
+        .into_iter()
+        .filter(|i| !i.is_empty())
+        .collect()
+    }
+
+    /// The smallest interval covering both
+    pub fn hull(&self, other: &Interval<T>) -> Interval<T> {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        Interval::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+impl<T> Interval<T>
+where
+    T: Copy + Ord + Zero + Sub<Output = T>,
+{
+    pub fn len(&self) -> T {
+        if self.is_empty() {
+            T::zero()
+        } else {
+            self.end - self.start
+        }
+    }
+
+    /// Image of `self` under the offset that moves `from` onto `to`.
+    /// Unsigned types require `self.start >= from`.
+    pub fn translate(&self, from: T, to: T) -> Interval<T> {
+        Interval::new(self.start - from + to, self.end - from + to)
+    }
+}
+
+/// A set of values stored as sorted, disjoint, non-adjacent intervals
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet { intervals: vec![] }
+    }
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Copy + Ord,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an interval, merging it with every interval it touches
+    pub fn insert(&mut self, interval: Interval<T>) {
+        if interval.is_empty() {
+            return;
+        }
+        let first = self.intervals.partition_point(|i| i.end < interval.start);
+        let last = self.intervals.partition_point(|i| i.start <= interval.end);
+        let merged = self.intervals[first..last]
+            .iter()
+            .fold(interval, |acc, i| acc.hull(i));
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn contains(&self, val: T) -> bool {
+        let idx = self.intervals.partition_point(|i| i.end <= val);
+        self.intervals.get(idx).is_some_and(|i| i.contains(val))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Smallest value in the set
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval<T>> {
+        self.intervals.iter()
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut out = self.clone();
+        other.iter().for_each(|i| out.insert(*i));
+        out
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        self.iter()
+            .flat_map(|a| other.iter().filter_map(move |b| a.intersection(b)))
+            .collect()
+    }
+
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        self.iter()
+            .flat_map(|a| {
+                other.iter().fold(vec![*a], |pieces, b| {
+                    pieces.iter().flat_map(|p| p.difference(b)).collect()
+                })
+            })
+            .collect()
+    }
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Copy + Ord + Zero + Sub<Output = T>,
+{
+    /// Number of values covered
+    pub fn total_len(&self) -> T {
+        self.iter().fold(T::zero(), |sum, i| sum + i.len())
+    }
+}
+
+impl<T> FromIterator<Interval<T>> for IntervalSet<T>
+where
+    T: Copy + Ord,
+{
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        iter.into_iter().for_each(|i| set.insert(i));
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn iv(start: i64, end: i64) -> Interval<i64> {
+        Interval::new(start, end)
+    }
+
+    fn set(intervals: &[(i64, i64)]) -> IntervalSet<i64> {
+        intervals.iter().map(|&(s, e)| iv(s, e)).collect()
+    }
+
+    fn pieces(set: &IntervalSet<i64>) -> Vec<(i64, i64)> {
+        set.iter().map(|i| (i.start, i.end)).collect()
+    }
+
+    #[test]
+    fn test_contains_bounds() {
+        let i = iv(3, 6);
+        assert!(!i.contains(2));
+        assert!(i.contains(3));
+        assert!(i.contains(5));
+        assert!(!i.contains(6));
+        assert!(!iv(3, 3).contains(3));
+        assert!(iv(3, 3).is_empty());
+        assert!(iv(4, 3).is_empty());
+        assert_eq!(0, iv(4, 3).len());
+        assert_eq!(3, i.len());
+    }
+
+    #[test]
+    fn test_overlap_and_touch() {
+        let a = iv(0, 5);
+        assert!(a.overlaps(&iv(4, 8)));
+        assert!(!a.overlaps(&iv(5, 8))); // Adjacent, no shared value
+        assert!(a.touches(&iv(5, 8)));
+        assert!(!a.touches(&iv(6, 8)));
+        assert!(a.overlaps(&iv(1, 2))); // Contained
+        assert!(iv(1, 2).overlaps(&a));
+        assert!(!a.overlaps(&iv(2, 2))); // Empty never overlaps
+        assert!(!a.touches(&iv(5, 5)));
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a = iv(0, 5);
+        assert_eq!(Some(iv(3, 5)), a.intersection(&iv(3, 9)));
+        assert_eq!(Some(iv(0, 5)), a.intersection(&iv(-1, 9)));
+        assert_eq!(Some(iv(4, 5)), a.intersection(&iv(4, 5)));
+        assert_eq!(None, a.intersection(&iv(5, 9)));
+        assert_eq!(None, a.intersection(&iv(-3, 0)));
+        assert_eq!(Some(a), a.intersection(&a));
+    }
+
+    #[test]
+    fn test_difference() {
+        let a = iv(0, 10);
+        assert_eq!(vec![iv(0, 3), iv(7, 10)], a.difference(&iv(3, 7)));
+        assert_eq!(vec![iv(5, 10)], a.difference(&iv(-5, 5)));
+        assert_eq!(vec![iv(0, 5)], a.difference(&iv(5, 15)));
+        assert_eq!(Vec::<Interval<i64>>::new(), a.difference(&iv(0, 10)));
+        assert_eq!(Vec::<Interval<i64>>::new(), a.difference(&iv(-1, 11)));
+        assert_eq!(vec![a], a.difference(&iv(10, 12))); // Adjacent
+        assert_eq!(vec![iv(1, 10)], a.difference(&iv(0, 1)));
+        assert_eq!(vec![iv(0, 9)], a.difference(&iv(9, 10)));
+        assert_eq!(vec![a], a.difference(&iv(4, 4))); // Empty
+    }
+
+    #[test]
+    fn test_hull() {
+        assert_eq!(iv(0, 10), iv(0, 2).hull(&iv(8, 10)));
+        assert_eq!(iv(8, 10), iv(3, 3).hull(&iv(8, 10)));
+    }
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(iv(52, 55), iv(50, 53).translate(50, 52));
+        let unsigned = Interval::<u64>::new(98, 100);
+        assert_eq!(Interval::new(50, 52), unsigned.translate(98, 50));
+    }
+
+    #[test]
+    fn test_set_merging() {
+        assert_eq!(vec![(0, 10)], pieces(&set(&[(0, 5), (5, 10)])));
+        assert_eq!(vec![(0, 10)], pieces(&set(&[(5, 10), (0, 6)])));
+        assert_eq!(vec![(0, 5), (6, 10)], pieces(&set(&[(6, 10), (0, 5)])));
+        assert_eq!(
+            vec![(0, 20)],
+            pieces(&set(&[(0, 2), (4, 6), (8, 10), (1, 20)]))
+        );
+        assert_eq!(
+            vec![(0, 2), (3, 9), (10, 12)],
+            pieces(&set(&[(0, 2), (4, 6), (10, 12), (3, 4), (6, 9), (5, 5)]))
+        );
+        assert!(set(&[(3, 3), (5, 1)]).is_empty());
+    }
+
+    #[test]
+    fn test_set_contains() {
+        let s = set(&[(0, 2), (5, 7)]);
+        assert!(s.contains(0));
+        assert!(s.contains(1));
+        assert!(!s.contains(2));
+        assert!(!s.contains(4));
+        assert!(s.contains(5));
+        assert!(!s.contains(7));
+        assert!(!s.contains(-1));
+        assert_eq!(Some(0), s.min());
+        assert_eq!(4, s.total_len());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[(0, 5), (10, 15)]);
+        let b = set(&[(3, 12), (14, 20)]);
+        assert_eq!(vec![(0, 20)], pieces(&a.union(&b)));
+        assert_eq!(
+            vec![(3, 5), (10, 12), (14, 15)],
+            pieces(&a.intersection(&b))
+        );
+        assert_eq!(vec![(0, 3), (12, 14)], pieces(&a.difference(&b)));
+        assert_eq!(vec![(5, 10), (15, 20)], pieces(&b.difference(&a)));
+
+        let empty = IntervalSet::new();
+        assert_eq!(a, a.union(&empty));
+        assert_eq!(empty, a.intersection(&empty));
+        assert_eq!(a, a.difference(&empty));
+        assert_eq!(empty, a.difference(&a));
+    }
+}
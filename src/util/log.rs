@@ -0,0 +1,267 @@
+//! Levelled trace output on stderr, filtered per puzzle day.
+//!
+//! The filter is read from the `AOC_LOG` environment variable unless `init` is
+//! called first. It is a comma separated list of a default level and `day=level`
+//! overrides, e.g. `AOC_LOG=warn,13=debug,puzzle_22=trace`. Nothing below `Warn`
+//! is printed by default.
+//!
+//! Puzzle code logs through the crate-level macros, which pick up the day from
+//! the calling module:
+//!
+//! ```
+//! use aoc32lib::debug;
+//! # let (i, score) = (3, 400);
+//! debug!("pattern {} scores {}", i, score);
+//! ```
+
+use anyhow::{Error, Result};
+use std::collections::BTreeMap;
+use std::fmt::{Arguments, Display};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::RwLock;
+
+pub const ENV_VAR: &str = "AOC_LOG";
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    const ALL: [Level; 5] = [
+        Level::Error,
+        Level::Warn,
+        Level::Info,
+        Level::Debug,
+        Level::Trace,
+    ];
+
+    /// Level for a number of `-v` flags on top of the default
+    pub fn from_verbosity(count: usize) -> Level {
+        Level::ALL[(Level::Warn as usize - 1 + count).min(Level::ALL.len() - 1)]
+    }
+}
+
+/// The number of `v`s in a verbosity flag such as `-v` or `-vvv`; None for any other argument
+pub fn verbosity_flag(arg: &str) -> Option<usize> {
+    let vs = arg.strip_prefix('-')?;
+    (!vs.is_empty() && vs.bytes().all(|b| b == b'v')).then_some(vs.len())
+}
+
+impl FromStr for Level {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Level::ALL
+            .into_iter()
+            .find(|level| level.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| Error::msg(format!("unknown log level {s:?}")))
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        })
+    }
+}
+
+/// Maximum level per day, falling back to a default for all other targets
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Filter {
+    pub default: Level,
+    pub days: BTreeMap<u32, Level>,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Filter {
+            default: Level::Warn,
+            days: BTreeMap::new(),
+        }
+    }
+}
+
+impl Filter {
+    /// The filter given by `AOC_LOG`; unset or invalid values give the default,
+    /// invalid ones after reporting why on stderr
+    pub fn from_env() -> Filter {
+        let Ok(spec) = std::env::var(ENV_VAR) else {
+            return Filter::default();
+        };
+        match spec.parse() {
+            Ok(filter) => filter,
+            Err(e) => {
+                eprintln!("ignoring {ENV_VAR}={spec:?}: {e}");
+                Filter::default()
+            }
+        }
+    }
+
+    pub fn enabled(&self, day: Option<u32>, level: Level) -> bool {
+        let max = day.and_then(|d| self.days.get(&d)).unwrap_or(&self.default);
+        level <= *max
+    }
+
+    fn max_level(&self) -> Level {
+        self.days.values().copied().fold(self.default, Level::max)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut filter = Filter::default();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            match part.split_once('=') {
+                Some((target, level)) => {
+                    let day = target_day(target.trim())
+                        .ok_or_else(|| Error::msg(format!("unknown log target {target:?}")))?;
+                    filter.days.insert(day, level.parse()?);
+                }
+                None => filter.default = part.parse()?,
+            }
+        }
+        Ok(filter)
+    }
+}
+
+static FILTER: RwLock<Option<Filter>> = RwLock::new(None);
+// Highest level any target can print; 0 until the filter is set up
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(0);
+
+/// Install `filter`, replacing any earlier one
+pub fn init(filter: Filter) {
+    let max = filter.max_level();
+    *FILTER.write().expect("log filter lock") = Some(filter);
+    MAX_LEVEL.store(max as u8, Ordering::Relaxed);
+}
+
+/// The day of a module path such as `aoc32lib::puzzle_13::test`, or a bare day number
+pub fn target_day(target: &str) -> Option<u32> {
+    target.parse().ok().or_else(|| {
+        target
+            .split("::")
+            .find_map(|part| part.strip_prefix("puzzle_")?.parse().ok())
+    })
+}
+
+pub fn enabled(target: &str, level: Level) -> bool {
+    let max = MAX_LEVEL.load(Ordering::Relaxed);
+    if max == 0 {
+        init(Filter::from_env());
+    } else if level as u8 > max {
+        return false;
+    }
+    FILTER
+        .read()
+        .expect("log filter lock")
+        .as_ref()
+        .is_some_and(|filter| filter.enabled(target_day(target), level))
+}
+
+/// Print a record unconditionally; use the macros to respect the filter
+pub fn write(target: &str, level: Level, args: Arguments) {
+    match target_day(target) {
+        Some(day) => eprintln!("[{level} day {day}] {args}"),
+        None => eprintln!("[{level} {target}] {args}"),
+    }
+}
+
+/// Log at the given level, if the filter lets it through for the calling module
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {{
+        let level = $level;
+        if $crate::util::log::enabled(module_path!(), level) {
+            $crate::util::log::write(module_path!(), level, format_args!($($arg)+));
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::log!($crate::util::log::Level::Error, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::log!($crate::util::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log!($crate::util::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log!($crate::util::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log!($crate::util::log::Level::Trace, $($arg)+) };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_filter() {
+        let filter: Filter = "info, 13=debug,puzzle_22=TRACE".parse().unwrap();
+        assert_eq!(Level::Info, filter.default);
+        assert_eq!(Some(&Level::Debug), filter.days.get(&13));
+        assert_eq!(Some(&Level::Trace), filter.days.get(&22));
+        assert_eq!(Level::Trace, filter.max_level());
+
+        assert_eq!(Filter::default(), "".parse().unwrap());
+        assert!("loud".parse::<Filter>().is_err());
+        assert!("pizza=debug".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn test_enabled() {
+        let filter: Filter = "13=trace,7=error".parse().unwrap();
+        assert!(filter.enabled(Some(13), Level::Trace));
+        assert!(!filter.enabled(Some(7), Level::Warn));
+        assert!(filter.enabled(Some(8), Level::Warn));
+        assert!(!filter.enabled(Some(8), Level::Info));
+        assert!(filter.enabled(None, Level::Error));
+    }
+
+    #[test]
+    fn test_target_day() {
+        assert_eq!(Some(13), target_day("aoc32lib::puzzle_13"));
+        assert_eq!(Some(7), target_day("aoc32lib::puzzle_7::test"));
+        assert_eq!(Some(22), target_day("22"));
+        assert_eq!(None, target_day("aoc32lib::util"));
+    }
+
+    #[test]
+    fn test_verbosity() {
+        assert_eq!(Level::Warn, Level::from_verbosity(0));
+        assert_eq!(Level::Info, Level::from_verbosity(1));
+        assert_eq!(Level::Trace, Level::from_verbosity(5));
+
+        assert_eq!(Some(1), verbosity_flag("-v"));
+        assert_eq!(Some(3), verbosity_flag("-vvv"));
+        assert_eq!(None, verbosity_flag("-"));
+        assert_eq!(None, verbosity_flag("--verbose"));
+        assert_eq!(None, verbosity_flag("--version"));
+        assert_eq!(None, verbosity_flag("-vx"));
+        assert_eq!(None, verbosity_flag("v"));
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Cache lookups served from memory versus computed
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+/// A cache of computed values keyed by solver state.
+///
+/// Recursive solvers go through `solve`, which hands the step function a
+/// callback for sub-states so every recursive call is cached too.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    stats: Stats,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo {
+            cache: HashMap::new(),
+            stats: Stats::default(),
+        }
+    }
+}
+
+impl<K, V> Memo<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cached value for `key`, computing it with `compute` on a miss
+    pub fn get_or_insert_with(&mut self, key: K, compute: impl FnOnce() -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+        self.stats.misses += 1;
+        let value = compute();
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    /// Evaluate the recursive function `step` at `key`.
+    /// `step` receives a callback that evaluates (and caches) other states.
+    pub fn solve<F>(&mut self, key: K, step: &F) -> V
+    where
+        K: Clone,
+        F: Fn(&mut dyn FnMut(K) -> V, &K) -> V,
+    {
+        if let Some(value) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+        self.stats.misses += 1;
+        let value = step(&mut |sub| self.solve(sub, step), &key);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Number of cached states
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Drop all cached values and reset the statistics
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.stats = Stats::default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fibonacci(n: u64, calls: &mut dyn FnMut(u64) -> u64) -> u64 {
+        if n < 2 {
+            n
+        } else {
+            calls(n - 1) + calls(n - 2)
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        let mut memo = Memo::new();
+        assert_eq!(
+            12586269025,
+            memo.solve(50, &|calls, n: &u64| fibonacci(*n, calls))
+        );
+        assert_eq!(51, memo.len());
+        assert_eq!(
+            Stats {
+                hits: 48,
+                misses: 51
+            },
+            memo.stats()
+        );
+
+        assert_eq!(55, memo.solve(10, &|calls, n: &u64| fibonacci(*n, calls)));
+        assert_eq!(49, memo.stats().hits);
+
+        memo.clear();
+        assert!(memo.is_empty());
+        assert_eq!(Stats::default(), memo.stats());
+    }
+
+    #[test]
+    fn test_get_or_insert_with() {
+        let mut memo = Memo::new();
+        let mut computed = 0;
+        for key in ["a", "b", "a", "a"] {
+            memo.get_or_insert_with(key, || {
+                computed += 1;
+                key.len()
+            });
+        }
+        assert_eq!(2, computed);
+        assert_eq!(Stats { hits: 2, misses: 2 }, memo.stats());
+    }
+}
@@ -0,0 +1,300 @@
+//! Reusable `nom` combinators for puzzle inputs.
+//!
+//! Parsers use `VerboseError` so that `parse_all` can point at the failing
+//! column of the offending line:
+//!
+//! ```text
+//! line 3, column 1: expected integer
+//!  3 | +x
+//!    | ^
+//! ```
+
+use super::{CellCodec, Grid};
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_while1};
+use nom::character::complete::{char, i64, line_ending, multispace0, one_of, space0, u64};
+use nom::combinator::{cut, eof, not, recognize};
+use nom::error::{context, VerboseError, VerboseErrorKind};
+use nom::multi::{many1_count, separated_list1};
+use nom::sequence::{separated_pair, terminated, tuple};
+use nom::{IResult, Offset, Parser};
+use std::fmt::Display;
+
+pub type PResult<'a, O> = IResult<&'a str, O, VerboseError<&'a str>>;
+
+pub fn unsigned(input: &str) -> PResult<'_, u64> {
+    context("unsigned integer", u64)(input)
+}
+
+pub fn signed(input: &str) -> PResult<'_, i64> {
+    context("integer", i64)(input)
+}
+
+// Any run of spaces and commas between list items
+fn list_separator(input: &str) -> PResult<'_, &str> {
+    recognize(many1_count(one_of(", ")))(input)
+}
+
+/// Integers separated by spaces and/or commas, e.g. `1 2 3` or `1,2, 3`
+pub fn unsigned_list(input: &str) -> PResult<'_, Vec<u64>> {
+    separated_list1(list_separator, unsigned)(input)
+}
+
+/// Signed integers separated by spaces and/or commas
+pub fn signed_list(input: &str) -> PResult<'_, Vec<i64>> {
+    separated_list1(list_separator, signed)(input)
+}
+
+/// A name made of letters, digits and underscores
+pub fn label(input: &str) -> PResult<'_, &str> {
+    context(
+        "label",
+        take_while1(|c: char| c.is_alphanumeric() || c == '_'),
+    )(input)
+}
+
+/// `key: value`, with optional spaces around the colon
+pub fn key_value<'a, K, V>(
+    key: impl Parser<&'a str, K, VerboseError<&'a str>>,
+    value: impl Parser<&'a str, V, VerboseError<&'a str>>,
+) -> impl FnMut(&'a str) -> PResult<'a, (K, V)> {
+    separated_pair(key, tuple((space0, char(':'), space0)), value)
+}
+
+/// `node -> a, b, c`; the targets are labels
+pub fn adjacency<'a, N>(
+    node: impl Parser<&'a str, N, VerboseError<&'a str>>,
+) -> impl FnMut(&'a str) -> PResult<'a, (N, Vec<&'a str>)> {
+    separated_pair(
+        node,
+        tuple((space0, context("arrow", tag("->")), space0)),
+        separated_list1(tuple((space0, char(','), space0)), label),
+    )
+}
+
+// A line ending followed by another non-blank line
+fn line_separator(input: &str) -> PResult<'_, &str> {
+    terminated(
+        line_ending,
+        not(alt((line_ending, recognize(tuple((multispace0, eof)))))),
+    )(input)
+}
+
+/// One or more lines, each parsed by `line`.
+/// Every non-blank line must parse, so errors point into the failing line.
+pub fn lines<'a, O>(
+    line: impl Parser<&'a str, O, VerboseError<&'a str>>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<O>> {
+    separated_list1(line_separator, cut(line))
+}
+
+/// Blocks separated by one or more blank lines
+pub fn blocks<'a, O>(
+    block: impl Parser<&'a str, O, VerboseError<&'a str>>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<O>> {
+    separated_list1(tuple((line_ending, many1_count(line_ending))), block)
+}
+
+fn failure<'a, O>(at: &'a str, what: &'static str) -> PResult<'a, O> {
+    Err(nom::Err::Failure(VerboseError {
+        errors: vec![(at, VerboseErrorKind::Context(what))],
+    }))
+}
+
+/// Rows of equal length, one character per cell, decoded by `codec`
+pub fn grid<'a, T, C>(codec: &'a C) -> impl FnMut(&'a str) -> PResult<'a, Grid<T>>
+where
+    C: CellCodec<T>,
+{
+    move |input: &'a str| {
+        let mut data = vec![];
+        let mut cols = None;
+        let row = |input: &'a str| -> PResult<'a, &'a str> {
+            let end = input.find(['\r', '\n']).unwrap_or(input.len());
+            if end == 0 {
+                return Err(nom::Err::Error(VerboseError {
+                    errors: vec![(input, VerboseErrorKind::Context("grid row"))],
+                }));
+            }
+            Ok((&input[end..], &input[..end]))
+        };
+        let (rest, rows) = separated_list1(line_ending, row)(input)?;
+        for row in rows.iter() {
+            let len = row.chars().count();
+            if *cols.get_or_insert(len) != len {
+                return failure(row, "row as long as the first");
+            }
+            for (i, c) in row.char_indices() {
+                match codec.decode(c) {
+                    Some(cell) => data.push(cell),
+                    None => return failure(&row[i..], "grid cell"),
+                }
+            }
+        }
+        let shape = (rows.len(), cols.unwrap_or(0));
+        Ok((rest, Grid { data, shape }))
+    }
+}
+
+/// A parse failure located in the original input
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+    pub message: String,
+    source_line: String,
+}
+
+impl ParseError {
+    /// An error at the start of `rest`, which must be a suffix of `input`
+    pub fn at(input: &str, rest: &str, message: impl Into<String>) -> Self {
+        let offset = input.offset(rest);
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find(['\r', '\n'])
+            .map_or(input.len(), |i| offset + i);
+        ParseError {
+            line: input[..line_start].matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            message: message.into(),
+            source_line: input[line_start..line_end].to_string(),
+        }
+    }
+
+    fn from_verbose(input: &str, err: VerboseError<&str>) -> Self {
+        let Some((rest, kind)) = err.errors.first() else {
+            return ParseError::at(input, input, "invalid input");
+        };
+        // Contexts wrapping the failing parser directly describe it best
+        let (here, enclosing): (Vec<_>, Vec<_>) = err
+            .errors
+            .iter()
+            .filter_map(|(at, kind)| match kind {
+                VerboseErrorKind::Context(what) => Some((at.len() == rest.len(), *what)),
+                _ => None,
+            })
+            .partition(|(same_place, _)| *same_place);
+        let expected = match (here.first(), kind) {
+            (Some((_, what)), _) => what.to_string(),
+            (None, VerboseErrorKind::Char(c)) => format!("{c:?}"),
+            (None, VerboseErrorKind::Nom(kind)) => kind.description().to_lowercase(),
+            (None, VerboseErrorKind::Context(what)) => what.to_string(),
+        };
+        let message = match enclosing.first() {
+            Some((_, what)) => format!("expected {expected} in {what}"),
+            None => format!("expected {expected}"),
+        };
+        ParseError::at(input, rest, message)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let gutter = self.line.to_string().len();
+        writeln!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )?;
+        writeln!(f, " {} | {}", self.line, self.source_line)?;
+        write!(f, " {:gutter$} | {:>col$}", "", "^", col = self.column)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Run `parser` over the whole of `input`; only trailing whitespace may be left over
+pub fn parse_all<'a, O>(
+    input: &'a str,
+    parser: impl Parser<&'a str, O, VerboseError<&'a str>>,
+) -> Result<O, ParseError> {
+    match terminated(parser, multispace0).parse(input) {
+        Ok(("", out)) => Ok(out),
+        Ok((rest, _)) => Err(ParseError::at(
+            input,
+            rest.trim_start(),
+            "unexpected trailing input",
+        )),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(ParseError::from_verbose(input, e)),
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::at(
+            input,
+            &input[input.len()..],
+            "incomplete input",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::{CharCodec, TableCodec};
+
+    #[test]
+    fn test_lists() {
+        assert_eq!(
+            vec![1, 22, 3],
+            parse_all("1 22   3", unsigned_list).unwrap()
+        );
+        assert_eq!(vec![1, 1, 3], parse_all("1,1,3\n", unsigned_list).unwrap());
+        assert_eq!(vec![-4, 0, 7], parse_all("-4, 0, 7", signed_list).unwrap());
+        assert!(parse_all("-4", unsigned_list).is_err());
+    }
+
+    #[test]
+    fn test_key_value() {
+        let parsed = parse_all("Card 12: 41 48", key_value(label, unsigned_list));
+        assert!(parsed.is_err()); // A label cannot contain spaces
+
+        let game = tuple((tag("Game "), unsigned));
+        let ((_, id), values) = parse_all("Game 7:3 4", key_value(game, unsigned_list)).unwrap();
+        assert_eq!(7, id);
+        assert_eq!(vec![3, 4], values);
+    }
+
+    #[test]
+    fn test_adjacency() {
+        let input = "a -> b, c\nd->e\n";
+        let parsed = parse_all(input, lines(adjacency(label))).unwrap();
+        assert_eq!(vec![("a", vec!["b", "c"]), ("d", vec!["e"])], parsed);
+
+        let err = parse_all("a -> b\nd => e", lines(adjacency(label))).unwrap_err();
+        assert_eq!((2, 3), (err.line, err.column));
+        assert_eq!("expected arrow", err.message);
+    }
+
+    #[test]
+    fn test_blocks_of_grids() {
+        let input = "#.\n.#\n\n\n##\n..\n##\n";
+        let grids = parse_all(input, blocks(grid(&CharCodec))).unwrap();
+        assert_eq!(2, grids.len());
+        assert_eq!((2, 2), grids[0].shape);
+        assert_eq!((3, 2), grids[1].shape);
+        assert_eq!('#', grids[1][2][1]);
+    }
+
+    #[test]
+    fn test_grid_errors() {
+        let codec = TableCodec::new(vec![('#', true), ('.', false)]);
+        let err = parse_all("#.#\n.x.\n", grid(&codec)).unwrap_err();
+        assert_eq!((2, 2), (err.line, err.column));
+        assert_eq!("expected grid cell", err.message);
+
+        let err = parse_all("#.#\n..\n", grid(&codec)).unwrap_err();
+        assert_eq!((2, 1), (err.line, err.column));
+    }
+
+    #[test]
+    fn test_caret_rendering() {
+        let err = parse_all("1 2\n3 -1 10\n+x", lines(signed_list)).unwrap_err();
+        assert_eq!(
+            "line 3, column 1: expected integer\n 3 | +x\n   | ^",
+            err.to_string()
+        );
+
+        let err = parse_all("1 2 x\n", unsigned_list).unwrap_err();
+        assert_eq!("unexpected trailing input", err.message);
+        assert_eq!((1, 5), (err.line, err.column));
+    }
+}
@@ -0,0 +1,221 @@
+//! Lattice polygon geometry: shoelace area, Pick's theorem and point-in-polygon.
+//!
+//! Vertices are `Pos` values with rows growing downwards. All arithmetic is done
+//! in checked `i128`, so every measure returns `None` instead of overflowing.
+
+use super::geom::Pos;
+use num::integer::gcd;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Orientation {
+    /// Clockwise as drawn on screen (rows growing downwards)
+    Clockwise,
+    CounterClockwise,
+    /// Zero area
+    Degenerate,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Containment {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// A closed polygon; the last vertex connects back to the first
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Polygon {
+    vertices: Vec<Pos>,
+}
+
+impl Polygon {
+    /// A repeated first vertex at the end is dropped
+    pub fn new(mut vertices: Vec<Pos>) -> Self {
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        Polygon { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Pos] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Pos, Pos)> + '_ {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(a, b)| (*a, *b))
+    }
+
+    /// Twice the signed shoelace area; positive for clockwise polygons
+    pub fn twice_signed_area(&self) -> Option<i128> {
+        self.edges().try_fold(0i128, |sum, (a, b)| {
+            let cross = (a.col as i128)
+                .checked_mul(b.row as i128)?
+                .checked_sub((b.col as i128).checked_mul(a.row as i128)?)?;
+            sum.checked_add(cross)
+        })
+    }
+
+    /// Enclosed area, rounded down for polygons with half-integer area
+    pub fn area(&self) -> Option<i128> {
+        Some(self.twice_signed_area()?.abs() / 2)
+    }
+
+    pub fn orientation(&self) -> Option<Orientation> {
+        Some(match self.twice_signed_area()?.signum() {
+            1 => Orientation::Clockwise,
+            -1 => Orientation::CounterClockwise,
+            _ => Orientation::Degenerate,
+        })
+    }
+
+    /// Number of lattice points on the edges
+    pub fn boundary_points(&self) -> Option<i128> {
+        self.edges().try_fold(0i128, |sum, (a, b)| {
+            let dr = (b.row as i128).checked_sub(a.row as i128)?.abs();
+            let dc = (b.col as i128).checked_sub(a.col as i128)?.abs();
+            sum.checked_add(gcd(dr, dc))
+        })
+    }
+
+    /// Number of lattice points strictly inside, by Pick's theorem.
+    /// `None` for degenerate polygons, such as collinear paths, where the theorem does not hold.
+    pub fn interior_points(&self) -> Option<i128> {
+        let twice_area = self.twice_signed_area()?.abs();
+        if twice_area == 0 {
+            return None;
+        }
+        let boundary = self.boundary_points()?;
+        let twice_interior = twice_area.checked_sub(boundary)?.checked_add(2)?;
+        if twice_interior < 0 {
+            return None;
+        }
+        Some(twice_interior / 2)
+    }
+
+    /// Interior and boundary lattice points together
+    pub fn lattice_points(&self) -> Option<i128> {
+        self.interior_points()?.checked_add(self.boundary_points()?)
+    }
+
+    /// Where `p` lies; `None` if the arithmetic overflows
+    pub fn contains(&self, p: Pos) -> Option<Containment> {
+        let (pr, pc) = (p.row as i128, p.col as i128);
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            let (ar, ac, br, bc) = (a.row as i128, a.col as i128, b.row as i128, b.col as i128);
+            let cross = bc
+                .checked_sub(ac)?
+                .checked_mul(pr.checked_sub(ar)?)?
+                .checked_sub(br.checked_sub(ar)?.checked_mul(pc.checked_sub(ac)?)?)?;
+            if cross == 0
+                && ar.min(br) <= pr
+                && pr <= ar.max(br)
+                && ac.min(bc) <= pc
+                && pc <= ac.max(bc)
+            {
+                return Some(Containment::Boundary);
+            }
+            // Cast a ray towards increasing columns; count edges crossing the point's row
+            if (ar > pr) != (br > pr) {
+                let crosses_right = if br > ar { cross > 0 } else { cross < 0 };
+                if crosses_right {
+                    inside = !inside;
+                }
+            }
+        }
+        Some(if inside {
+            Containment::Inside
+        } else {
+            Containment::Outside
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn polygon(points: &[(isize, isize)]) -> Polygon {
+        Polygon::new(points.iter().map(|&(r, c)| Pos::new(r, c)).collect())
+    }
+
+    #[test]
+    fn test_square() {
+        let square = polygon(&[(0, 0), (0, 4), (4, 4), (4, 0)]);
+        assert_eq!(Some(32), square.twice_signed_area());
+        assert_eq!(Some(16), square.area());
+        assert_eq!(Some(Orientation::Clockwise), square.orientation());
+        assert_eq!(Some(16), square.boundary_points());
+        assert_eq!(Some(9), square.interior_points());
+        assert_eq!(Some(25), square.lattice_points());
+
+        let reversed = polygon(&[(0, 0), (4, 0), (4, 4), (0, 4), (0, 0)]);
+        assert_eq!(4, reversed.vertices().len());
+        assert_eq!(Some(-32), reversed.twice_signed_area());
+        assert_eq!(Some(Orientation::CounterClockwise), reversed.orientation());
+        assert_eq!(Some(9), reversed.interior_points());
+    }
+
+    #[test]
+    fn test_triangle() {
+        let triangle = polygon(&[(0, 0), (0, 3), (3, 0)]);
+        assert_eq!(Some(9), triangle.twice_signed_area());
+        assert_eq!(Some(4), triangle.area());
+        assert_eq!(Some(9), triangle.boundary_points());
+        assert_eq!(Some(1), triangle.interior_points());
+    }
+
+    #[test]
+    fn test_degenerate() {
+        let line = polygon(&[(0, 0), (0, 5)]);
+        assert_eq!(Some(Orientation::Degenerate), line.orientation());
+        assert_eq!(Some(0), line.area());
+        assert_eq!(Some(10), line.boundary_points());
+        assert_eq!(None, line.interior_points());
+        assert_eq!(None, line.lattice_points());
+
+        let collinear = polygon(&[(0, 0), (3, 3), (6, 6)]);
+        assert_eq!(Some(Orientation::Degenerate), collinear.orientation());
+        assert_eq!(None, collinear.interior_points());
+
+        // Pick's formula would give (0 - 0 + 2) / 2 = 1 for these
+        assert_eq!(None, polygon(&[]).interior_points());
+        assert_eq!(None, polygon(&[(2, 3)]).interior_points());
+        assert_eq!(None, polygon(&[(2, 3)]).lattice_points());
+    }
+
+    #[test]
+    fn test_huge_coordinates() {
+        let big = isize::MAX / 2;
+        let square = polygon(&[(0, 0), (0, big), (big, big), (big, 0)]);
+        let expected = (big as i128) * (big as i128);
+        assert_eq!(Some(expected), square.area());
+        assert_eq!(Some(4 * big as i128), square.boundary_points());
+
+        let extreme = polygon(&[
+            (isize::MIN, isize::MIN),
+            (isize::MIN, isize::MAX),
+            (isize::MAX, isize::MAX),
+        ]);
+        assert_eq!(None, extreme.contains(Pos::new(isize::MAX, isize::MIN)));
+        assert_eq!(None, extreme.twice_signed_area());
+    }
+
+    #[test]
+    fn test_contains() {
+        // An L shape
+        let l = polygon(&[(0, 0), (0, 2), (2, 2), (2, 4), (4, 4), (4, 0)]);
+        assert_eq!(Some(Containment::Inside), l.contains(Pos::new(1, 1)));
+        assert_eq!(Some(Containment::Inside), l.contains(Pos::new(3, 3)));
+        assert_eq!(Some(Containment::Outside), l.contains(Pos::new(1, 3)));
+        assert_eq!(Some(Containment::Outside), l.contains(Pos::new(-1, 1)));
+        assert_eq!(Some(Containment::Outside), l.contains(Pos::new(2, 5)));
+        assert_eq!(Some(Containment::Boundary), l.contains(Pos::new(0, 0)));
+        assert_eq!(Some(Containment::Boundary), l.contains(Pos::new(2, 3)));
+        assert_eq!(Some(Containment::Boundary), l.contains(Pos::new(1, 2)));
+        assert_eq!(Some(Containment::Inside), l.contains(Pos::new(3, 1)));
+    }
+}
@@ -0,0 +1,175 @@
+use super::Grid;
+use std::collections::VecDeque;
+
+/// Which cells count as adjacent when growing a region
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Connectivity {
+    /// Orthogonal neighbours only
+    Four,
+    /// Orthogonal and diagonal neighbours
+    Eight,
+}
+
+/// A connected region found by `Grid::components`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Region {
+    pub label: usize,
+    pub size: usize,
+    /// Inclusive (min, max) corners as (row, col)
+    pub bounds: ((usize, usize), (usize, usize)),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Components {
+    /// Region label per cell, `None` for impassable cells
+    pub labels: Grid<Option<usize>>,
+    /// Regions indexed by their label
+    pub regions: Vec<Region>,
+}
+
+impl<T> Grid<T> {
+    fn connected(&self, pos: (usize, usize), connectivity: Connectivity) -> Vec<(usize, usize)> {
+        match connectivity {
+            Connectivity::Four => self.neighbours(pos),
+            Connectivity::Eight => {
+                let (r, c) = (pos.0 as isize, pos.1 as isize);
+                (r - 1..=r + 1)
+                    .flat_map(|nr| (c - 1..=c + 1).map(move |nc| (nr, nc)))
+                    .filter(|&(nr, nc)| (nr, nc) != (r, c))
+                    .filter(|&(nr, nc)| {
+                        nr >= 0
+                            && nc >= 0
+                            && (nr as usize) < self.shape.0
+                            && (nc as usize) < self.shape.1
+                    })
+                    .map(|(nr, nc)| (nr as usize, nc as usize))
+                    .collect()
+            }
+        }
+    }
+
+    /// All cells reachable from `seed` through passable cells, in breadth-first order.
+    /// Returns nothing if the seed itself is not passable.
+    pub fn flood_fill<P>(
+        &self,
+        seed: (usize, usize),
+        connectivity: Connectivity,
+        passable: P,
+    ) -> Vec<(usize, usize)>
+    where
+        P: Fn(&T) -> bool,
+    {
+        let mut visited = Grid::new(self.shape, false);
+        self.fill_from(seed, connectivity, &passable, &mut visited)
+    }
+
+    /// Label every connected region of passable cells
+    pub fn components<P>(&self, connectivity: Connectivity, passable: P) -> Components
+    where
+        P: Fn(&T) -> bool,
+    {
+        let mut visited = Grid::new(self.shape, false);
+        let mut labels = Grid::new(self.shape, None);
+        let mut regions = vec![];
+        for row in 0..self.shape.0 {
+            for col in 0..self.shape.1 {
+                if visited[row][col] {
+                    continue;
+                }
+                let cells = self.fill_from((row, col), connectivity, &passable, &mut visited);
+                if cells.is_empty() {
+                    continue;
+                }
+                let label = regions.len();
+                let mut bounds = ((row, col), (row, col));
+                for &(r, c) in cells.iter() {
+                    labels[r][c] = Some(label);
+                    bounds.0 = (bounds.0 .0.min(r), bounds.0 .1.min(c));
+                    bounds.1 = (bounds.1 .0.max(r), bounds.1 .1.max(c));
+                }
+                regions.push(Region {
+                    label,
+                    size: cells.len(),
+                    bounds,
+                });
+            }
+        }
+        Components { labels, regions }
+    }
+
+    // Iterative so large regions do not overflow the stack
+    fn fill_from<P>(
+        &self,
+        seed: (usize, usize),
+        connectivity: Connectivity,
+        passable: &P,
+        visited: &mut Grid<bool>,
+    ) -> Vec<(usize, usize)>
+    where
+        P: Fn(&T) -> bool,
+    {
+        let mut cells = vec![];
+        match self.get(seed) {
+            Some(cell) if passable(cell) && !visited[seed.0][seed.1] => {}
+            _ => return cells,
+        }
+        let mut queue = VecDeque::from([seed]);
+        visited[seed.0][seed.1] = true;
+        while let Some(pos) = queue.pop_front() {
+            cells.push(pos);
+            for (r, c) in self.connected(pos, connectivity) {
+                if !visited[r][c] && passable(&self[r][c]) {
+                    visited[r][c] = true;
+                    queue.push_back((r, c));
+                }
+            }
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DATA: &str = "\
+..#..
+..#..
+##.##
+..#..";
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = Grid::<char>::from_table_data(DATA);
+        let open = |c: &char| *c == '.';
+
+        let mut filled = grid.flood_fill((0, 0), Connectivity::Four, open);
+        filled.sort();
+        assert_eq!(vec![(0, 0), (0, 1), (1, 0), (1, 1)], filled);
+
+        assert_eq!(13, grid.flood_fill((0, 0), Connectivity::Eight, open).len());
+        assert!(grid.flood_fill((0, 2), Connectivity::Four, open).is_empty());
+    }
+
+    #[test]
+    fn test_components() {
+        let grid = Grid::<char>::from_table_data(DATA);
+        let components = grid.components(Connectivity::Four, |c| *c == '.');
+        let sizes: Vec<usize> = components.regions.iter().map(|r| r.size).collect();
+        assert_eq!(vec![4, 4, 1, 2, 2], sizes);
+        assert_eq!(((0, 3), (1, 4)), components.regions[1].bounds);
+        assert_eq!(Some(2), components.labels[2][2]);
+        assert_eq!(None, components.labels[0][2]);
+
+        let components = grid.components(Connectivity::Eight, |c| *c == '.');
+        assert_eq!(1, components.regions.len());
+        assert_eq!(((0, 0), (3, 4)), components.regions[0].bounds);
+    }
+
+    #[test]
+    fn test_large_fill() {
+        let grid = Grid::new((1000, 1000), 0u8);
+        let filled = grid.flood_fill((500, 500), Connectivity::Four, |_| true);
+        assert_eq!(1_000_000, filled.len());
+    }
+}
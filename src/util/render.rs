@@ -0,0 +1,295 @@
+//! Render grids to PPM and PNG images for visual debugging, and colour text
+//! for the terminal.
+//!
+//! PNG output uses stored (uncompressed) deflate blocks, so no image or
+//! compression library is needed.
+
+use super::Grid;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+
+/// Linear black-to-white shade of `value` on a scale of `0..=max`
+pub fn gray(value: u64, max: u64) -> Rgb {
+    let v = (value.min(max) * 255 / max.max(1)) as u8;
+    [v, v, v]
+}
+
+/// Blue-to-red heat map shade of `value` on a scale of `0..=max`
+pub fn heat(value: u64, max: u64) -> Rgb {
+    let v = (value.min(max) * 255 / max.max(1)) as u8;
+    [v, 0, 255 - v]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ppm,
+    Png,
+}
+
+impl Format {
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Ppm => "ppm",
+            Format::Png => "png",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    /// Draw every cell as a `scale` x `scale` square of the colour picked by `colour`
+    pub fn from_grid<T, F>(grid: &Grid<T>, scale: usize, colour: F) -> Self
+    where
+        F: Fn(&T) -> Rgb,
+    {
+        let mut image = Image::new(grid.shape.1 * scale, grid.shape.0 * scale, BLACK);
+        for row in 0..grid.shape.0 {
+            for col in 0..grid.shape.1 {
+                let rgb = colour(&grid[row][col]);
+                for y in row * scale..(row + 1) * scale {
+                    for x in col * scale..(col + 1) * scale {
+                        image.set(x, y, rgb);
+                    }
+                }
+            }
+        }
+        image
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, rgb: Rgb) {
+        self.pixels[y * self.width + x] = rgb;
+    }
+
+    /// Binary PPM (P6)
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.pixels.iter().flatten());
+        out
+    }
+
+    /// 8-bit RGB PNG without compression
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut header = vec![];
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        header.extend([8, 2, 0, 0, 0]); // Bit depth, RGB, deflate, no filter, no interlace
+
+        let mut scanlines = Vec::with_capacity(self.height * (self.width * 3 + 1));
+        for row in self.pixels.chunks(self.width.max(1)) {
+            scanlines.push(0); // Filter type: none
+            scanlines.extend(row.iter().flatten());
+        }
+
+        let mut out = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        png_chunk(&mut out, b"IHDR", &header);
+        png_chunk(&mut out, b"IDAT", &zlib_stored(&scanlines));
+        png_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>, format: Format) -> io::Result<()> {
+        let bytes = match format {
+            Format::Ppm => self.to_ppm(),
+            Format::Png => self.to_png(),
+        };
+        fs::write(path, bytes)
+    }
+}
+
+/// Writes numbered frames (`prefix_00000.png`, `prefix_00001.png`, ...) into a directory
+#[derive(Debug)]
+pub struct FrameWriter {
+    dir: PathBuf,
+    prefix: String,
+    format: Format,
+    frame: usize,
+}
+
+impl FrameWriter {
+    pub fn new(dir: impl AsRef<Path>, prefix: &str, format: Format) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(FrameWriter {
+            dir: dir.as_ref().to_path_buf(),
+            prefix: prefix.to_string(),
+            format,
+            frame: 0,
+        })
+    }
+
+    /// Write the next frame and return its path
+    pub fn write(&mut self, image: &Image) -> io::Result<PathBuf> {
+        let name = format!(
+            "{}_{:05}.{}",
+            self.prefix,
+            self.frame,
+            self.format.extension()
+        );
+        let path = self.dir.join(name);
+        image.write(&path, self.format)?;
+        self.frame += 1;
+        Ok(path)
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frame
+    }
+}
+
+/// Terminal foreground colours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ansi {
+    Red = 31,
+    Green = 32,
+    Yellow = 33,
+    Blue = 34,
+    Magenta = 35,
+    Cyan = 36,
+    Gray = 90,
+}
+
+impl Ansi {
+    /// `text` in this colour, resetting afterwards
+    pub fn paint(self, text: &str) -> String {
+        format!("\x1b[{}m{text}\x1b[0m", self as u8)
+    }
+
+    pub fn paint_bold(self, text: &str) -> String {
+        format!("\x1b[1;{}m{text}\x1b[0m", self as u8)
+    }
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+// Zlib stream made of stored deflate blocks of at most 0xffff bytes each
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ansi() {
+        assert_eq!("\x1b[32m42\x1b[0m", Ansi::Green.paint("42"));
+        assert_eq!("\x1b[1;90m*\x1b[0m", Ansi::Gray.paint_bold("*"));
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(0xae42_6082, crc32(b"IEND"));
+        assert_eq!(0x11e6_0398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn test_from_grid_scaled() {
+        let grid = Grid::<char>::from_table_data("#.\n.#");
+        let image = Image::from_grid(&grid, 3, |c| if *c == '#' { WHITE } else { BLACK });
+        assert_eq!((6, 6), (image.width, image.height));
+        assert_eq!(WHITE, image.get(2, 2));
+        assert_eq!(BLACK, image.get(3, 2));
+        assert_eq!(WHITE, image.get(5, 5));
+    }
+
+    #[test]
+    fn test_ppm() {
+        let image = Image::new(2, 1, [1, 2, 3]);
+        assert_eq!(
+            b"P6\n2 1\n255\n\x01\x02\x03\x01\x02\x03".to_vec(),
+            image.to_ppm()
+        );
+    }
+
+    #[test]
+    fn test_png_layout() {
+        let image = Image::new(300, 300, heat(3, 10));
+        let png = image.to_png();
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &png[..8]);
+        assert_eq!(b"IHDR", &png[12..16]);
+        assert_eq!(300u32.to_be_bytes(), png[16..20]);
+        assert_eq!(b"IEND", &png[png.len() - 8..png.len() - 4]);
+
+        // Raw data: 300 rows of 1 filter byte + 900 colour bytes, split into five stored blocks
+        let raw = 300 * 901;
+        let idat_len = u32::from_be_bytes([png[33], png[34], png[35], png[36]]) as usize;
+        assert_eq!(2 + 5 * 5 + raw + 4, idat_len);
+    }
+
+    #[test]
+    fn test_frame_writer() {
+        let dir = std::env::temp_dir().join(format!("aoc_render_{}", std::process::id()));
+        let mut frames = FrameWriter::new(&dir, "step", Format::Ppm).unwrap();
+        let image = Image::new(1, 1, gray(1, 2));
+        frames.write(&image).unwrap();
+        let path = frames.write(&image).unwrap();
+        assert_eq!(2, frames.frames());
+        assert_eq!(dir.join("step_00001.ppm"), path);
+        assert_eq!(image.to_ppm(), fs::read(&path).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,252 @@
+use super::Grid;
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::ops::Index;
+
+pub type SignedPosition = (isize, isize);
+
+/// Orthogonal neighbours on an unbounded plane (N, E, S, W)
+fn plane_neighbours(pos: SignedPosition) -> Vec<SignedPosition> {
+    let (r, c) = pos;
+    vec![(r - 1, c), (r, c + 1), (r + 1, c), (r, c - 1)]
+}
+
+/// A grid on the unbounded plane, keyed by signed (row, col) coordinates.
+/// Cells that were never written read as the default value.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SparseGrid<T> {
+    cells: BTreeMap<SignedPosition, T>,
+    default: T,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new(default: T) -> Self {
+        SparseGrid {
+            cells: BTreeMap::new(),
+            default,
+        }
+    }
+
+    pub fn get(&self, pos: SignedPosition) -> &T {
+        self.cells.get(&pos).unwrap_or(&self.default)
+    }
+
+    pub fn insert(&mut self, pos: SignedPosition, value: T) -> Option<T> {
+        self.cells.insert(pos, value)
+    }
+
+    pub fn remove(&mut self, pos: SignedPosition) -> Option<T> {
+        self.cells.remove(&pos)
+    }
+
+    /// Number of explicitly stored cells
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Iterate the explicitly stored cells in (row, col) order
+    pub fn iter(&self) -> impl Iterator<Item = (&SignedPosition, &T)> {
+        self.cells.iter()
+    }
+
+    /// Inclusive (min, max) corners of all stored cells
+    pub fn bounds(&self) -> Option<(SignedPosition, SignedPosition)> {
+        let mut keys = self.cells.keys();
+        let first = *keys.next()?;
+        Some(keys.fold((first, first), |(min, max), &(r, c)| {
+            ((min.0.min(r), min.1.min(c)), (max.0.max(r), max.1.max(c)))
+        }))
+    }
+
+    pub fn neighbours(&self, pos: SignedPosition) -> Vec<SignedPosition> {
+        plane_neighbours(pos)
+    }
+}
+
+impl<T> SparseGrid<T>
+where
+    T: Clone,
+{
+    /// Mutable access to a cell, materializing the default if it was never written
+    pub fn get_mut(&mut self, pos: SignedPosition) -> &mut T {
+        self.cells
+            .entry(pos)
+            .or_insert_with(|| self.default.clone())
+    }
+
+    /// Copy every cell of a dense grid, placing `grid[0][0]` at (0, 0)
+    pub fn from_grid(grid: &Grid<T>, default: T) -> Self {
+        let mut sparse = SparseGrid::new(default);
+        for row in 0..grid.shape.0 {
+            for col in 0..grid.shape.1 {
+                sparse.insert((row as isize, col as isize), grid[row][col].clone());
+            }
+        }
+        sparse
+    }
+}
+
+impl<T> Index<SignedPosition> for SparseGrid<T> {
+    type Output = T;
+
+    fn index(&self, index: SignedPosition) -> &Self::Output {
+        self.get(index)
+    }
+}
+
+impl<T> Display for SparseGrid<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some((min, max)) = self.bounds() {
+            for row in min.0..=max.0 {
+                for col in min.1..=max.1 {
+                    f.write_fmt(format_args!("{} ", self.get((row, col))))?;
+                }
+                f.write_str("\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An infinite view that repeats a dense grid in every direction
+#[derive(Debug)]
+pub struct TiledGrid<'a, T> {
+    grid: &'a Grid<T>,
+}
+
+impl<'a, T> TiledGrid<'a, T> {
+    /// Fails for a grid without cells, which cannot tile the plane
+    pub fn new(grid: &'a Grid<T>) -> Result<Self> {
+        if grid.shape.0 == 0 || grid.shape.1 == 0 {
+            bail!(
+                "cannot tile an empty {}x{} grid",
+                grid.shape.0,
+                grid.shape.1
+            );
+        }
+        Ok(TiledGrid { grid })
+    }
+
+    /// Map a signed position onto the underlying grid
+    pub fn wrap(&self, pos: SignedPosition) -> (usize, usize) {
+        (
+            pos.0.rem_euclid(self.grid.shape.0 as isize) as usize,
+            pos.1.rem_euclid(self.grid.shape.1 as isize) as usize,
+        )
+    }
+
+    /// Which copy of the grid a position falls into, (0, 0) being the original
+    pub fn tile(&self, pos: SignedPosition) -> SignedPosition {
+        (
+            pos.0.div_euclid(self.grid.shape.0 as isize),
+            pos.1.div_euclid(self.grid.shape.1 as isize),
+        )
+    }
+
+    pub fn get(&self, pos: SignedPosition) -> &'a T {
+        let (row, col) = self.wrap(pos);
+        &self.grid[row][col]
+    }
+
+    pub fn neighbours(&self, pos: SignedPosition) -> Vec<SignedPosition> {
+        plane_neighbours(pos)
+    }
+}
+
+impl<'a, T> Index<SignedPosition> for TiledGrid<'a, T> {
+    type Output = T;
+
+    fn index(&self, index: SignedPosition) -> &Self::Output {
+        self.get(index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DATA: &str = "123\n456\n789\nxyz";
+
+    #[test]
+    fn test_sparse_default() {
+        let mut grid = SparseGrid::new('.');
+        grid.insert((-5, 3), '#');
+        assert_eq!('#', grid[(-5, 3)]);
+        assert_eq!('.', grid[(0, 0)]);
+        assert_eq!(1, grid.len());
+
+        *grid.get_mut((2, -2)) = '#';
+        assert_eq!('#', grid[(2, -2)]);
+        assert_eq!(2, grid.len());
+    }
+
+    #[test]
+    fn test_sparse_bounds() {
+        let mut grid = SparseGrid::new(0);
+        assert_eq!(None, grid.bounds());
+        grid.insert((-5, 3), 1);
+        grid.insert((2, -7), 1);
+        grid.insert((0, 10), 1);
+        assert_eq!(Some(((-5, -7), (2, 10))), grid.bounds());
+    }
+
+    #[test]
+    fn test_sparse_display() {
+        let mut grid = SparseGrid::new('.');
+        grid.insert((-1, -1), '#');
+        grid.insert((0, 1), '#');
+        assert_eq!("# . . \n. . # \n", grid.to_string());
+    }
+
+    #[test]
+    fn test_sparse_from_grid() {
+        let grid = Grid::<char>::from_table_data(DATA);
+        let sparse = SparseGrid::from_grid(&grid, ' ');
+        assert_eq!(12, sparse.len());
+        assert_eq!('y', sparse[(3, 1)]);
+        assert_eq!(' ', sparse[(4, 1)]);
+    }
+
+    #[test]
+    fn test_tiled_wrap() {
+        let grid = Grid::<char>::from_table_data(DATA);
+        let tiled = TiledGrid::new(&grid).unwrap();
+        assert_eq!('1', tiled[(0, 0)]);
+        assert_eq!('1', tiled[(4, 3)]);
+        assert_eq!('z', tiled[(-1, -1)]);
+        assert_eq!('4', tiled[(-7, -6)]);
+        assert_eq!((-1, -1), tiled.tile((-1, -1)));
+        assert_eq!((1, 0), tiled.tile((4, 2)));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::<char>::from_table_data(DATA);
+        assert_eq!(vec![(0, 1), (1, 0)], grid.neighbours((0, 0)));
+        assert_eq!(
+            vec![(0, 1), (1, 2), (2, 1), (1, 0)],
+            grid.neighbours((1, 1))
+        );
+        let tiled = TiledGrid::new(&grid).unwrap();
+        assert_eq!(
+            vec![(-1, 0), (0, 1), (1, 0), (0, -1)],
+            tiled.neighbours((0, 0))
+        );
+    }
+
+    #[test]
+    fn test_tiled_empty() {
+        let grid = Grid::new((0, 3), '.');
+        assert!(TiledGrid::new(&grid).is_err());
+        let grid = Grid::new((2, 0), '.');
+        assert!(TiledGrid::new(&grid).is_err());
+    }
+}
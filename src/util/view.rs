@@ -0,0 +1,188 @@
+use super::Grid;
+use std::ops::Index;
+
+/// A borrowed column of a grid
+#[derive(Debug)]
+pub struct Column<'a, T> {
+    grid: &'a Grid<T>,
+    col: usize,
+}
+
+// Manual impls: views are copyable regardless of `T`
+impl<'a, T> Clone for Column<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for Column<'a, T> {}
+
+impl<'a, T> Column<'a, T> {
+    pub fn len(&self) -> usize {
+        self.grid.shape.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &'a T> + ExactSizeIterator {
+        let (grid, col) = (self.grid, self.col);
+        (0..grid.shape.0).map(move |row| &grid[row][col])
+    }
+}
+
+impl<'a, T> Index<usize> for Column<'a, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.grid[index][self.col]
+    }
+}
+
+/// A borrowed rectangular region of a grid, indexed relative to its origin
+#[derive(Debug)]
+pub struct SubGrid<'a, T> {
+    grid: &'a Grid<T>,
+    origin: (usize, usize),
+    pub shape: (usize, usize),
+}
+
+impl<'a, T> Clone for SubGrid<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for SubGrid<'a, T> {}
+
+impl<'a, T> SubGrid<'a, T> {
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &'a [T]> + ExactSizeIterator {
+        let view = *self;
+        (0..self.shape.0).map(move |row| view.row(row))
+    }
+
+    pub fn enumerate_cells(&self) -> impl Iterator<Item = ((usize, usize), &'a T)> {
+        self.rows().enumerate().flat_map(|(row, cells)| {
+            cells
+                .iter()
+                .enumerate()
+                .map(move |(col, c)| ((row, col), c))
+        })
+    }
+
+    fn row(&self, row: usize) -> &'a [T] {
+        assert!(row < self.shape.0, "row {row} outside sub-grid");
+        let start = self.origin.1;
+        &self.grid[self.origin.0 + row][start..start + self.shape.1]
+    }
+}
+
+impl<'a, T> Index<usize> for SubGrid<'a, T> {
+    type Output = [T];
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.row(index)
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn column(&self, col: usize) -> Column<'_, T> {
+        assert!(col < self.shape.1, "column {col} outside grid");
+        Column { grid: self, col }
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        (0..self.shape.0).map(|row| &self[row])
+    }
+
+    pub fn columns(&self) -> impl DoubleEndedIterator<Item = Column<'_, T>> + ExactSizeIterator {
+        (0..self.shape.1).map(|col| self.column(col))
+    }
+
+    /// View of the `shape` sized region starting at `origin` (row, col)
+    pub fn sub_grid(&self, origin: (usize, usize), shape: (usize, usize)) -> SubGrid<'_, T> {
+        assert!(
+            origin.0 + shape.0 <= self.shape.0 && origin.1 + shape.1 <= self.shape.1,
+            "sub-grid {shape:?} at {origin:?} exceeds grid {:?}",
+            self.shape
+        );
+        SubGrid {
+            grid: self,
+            origin,
+            shape,
+        }
+    }
+
+    /// All cells in row-major order with their (row, col) position
+    pub fn enumerate_cells(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let cols = self.shape.1;
+        self.data
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| ((i / cols, i % cols), cell))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DATA: &str = "123\n456\n789\nxyz";
+
+    #[test]
+    fn test_columns() {
+        let grid = Grid::<char>::from_table_data(DATA);
+        let col = grid.column(1);
+        assert_eq!(4, col.len());
+        assert_eq!('8', col[2]);
+        assert_eq!("258y", col.iter().collect::<String>());
+        assert_eq!("y852", col.iter().rev().collect::<String>());
+
+        let columns: Vec<String> = grid.columns().map(|c| c.iter().collect()).collect();
+        assert_eq!(vec!["147x", "258y", "369z"], columns);
+    }
+
+    #[test]
+    fn test_rows() {
+        let grid = Grid::<char>::from_table_data(DATA);
+        let rows: Vec<String> = grid.rows().map(|r| r.iter().collect()).collect();
+        assert_eq!(vec!["123", "456", "789", "xyz"], rows);
+
+        let empty_rows: Grid<char> = Grid {
+            data: vec![],
+            shape: (3, 0),
+        };
+        assert_eq!(3, empty_rows.rows().len());
+        assert!(empty_rows.rows().all(|r| r.is_empty()));
+        assert_eq!(0, empty_rows.columns().len());
+    }
+
+    #[test]
+    fn test_sub_grid() {
+        let grid = Grid::<char>::from_table_data(DATA);
+        let sub = grid.sub_grid((1, 1), (3, 2));
+        assert_eq!(['5', '6'], sub[0]);
+        assert_eq!('y', sub[2][0]);
+        let rows: Vec<String> = sub.rows().map(|r| r.iter().collect()).collect();
+        assert_eq!(vec!["56", "89", "yz"], rows);
+        assert_eq!(Some(((2, 1), &'z')), sub.enumerate_cells().last());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sub_grid_out_of_bounds() {
+        let grid = Grid::<char>::from_table_data(DATA);
+        grid.sub_grid((2, 2), (3, 1));
+    }
+
+    #[test]
+    fn test_enumerate_cells() {
+        let grid = Grid::<char>::from_table_data(DATA);
+        let cells: Vec<((usize, usize), &char)> = grid.enumerate_cells().collect();
+        assert_eq!(12, cells.len());
+        assert_eq!(((0, 0), &'1'), cells[0]);
+        assert_eq!(((1, 2), &'6'), cells[5]);
+        assert_eq!(((3, 2), &'z'), cells[11]);
+    }
+}
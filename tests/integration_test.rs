@@ -30,6 +30,7 @@ fn test_puzzle_9() {
 
 #[test]
 fn test_puzzle_10() {
+    assert_eq!(495, aoc32lib::puzzle_10::puzzle_10_2());
     assert_eq!(6786, aoc32lib::puzzle_10::puzzle_10_1());
 }
 